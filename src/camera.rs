@@ -0,0 +1,91 @@
+// module for the scrolling view onto maps bigger than the screen
+use crate::{
+    components::{Hidden, Position, Renderable},
+    map::{draw_map, Map},
+};
+use bracket_lib::prelude::*;
+use specs::prelude::*;
+
+/// size of the part of the screen the map is drawn into, the rest belongs to the ui
+pub const VIEW_WIDTH: i32 = 80;
+pub const VIEW_HEIGHT: i32 = 43;
+
+/// Which slice of the map is on screen.
+/// Cheap to build, so it's worked out fresh from the player's position whenever it's needed.
+#[derive(Copy, Clone)]
+pub struct Camera {
+    /// map coordinates of the top-left screen tile
+    pub min_x: i32,
+    pub min_y: i32,
+}
+
+impl Camera {
+    /// a view with center in the middle of the screen
+    pub fn centered_on(center: Point) -> Camera {
+        Camera {
+            min_x: center.x - VIEW_WIDTH / 2,
+            min_y: center.y - VIEW_HEIGHT / 2,
+        }
+    }
+
+    /// the view following the player
+    pub fn from_player(ecs: &World) -> Camera {
+        Camera::centered_on(*ecs.fetch::<Point>())
+    }
+
+    /// where a map tile is drawn, None if it's off screen
+    pub fn world_to_screen(&self, x: i32, y: i32) -> Option<Point> {
+        let screen_x = x - self.min_x;
+        let screen_y = y - self.min_y;
+        if screen_x < 0 || screen_x >= VIEW_WIDTH || screen_y < 0 || screen_y >= VIEW_HEIGHT {
+            return None;
+        }
+        Some(Point::new(screen_x, screen_y))
+    }
+
+    /// Which map tile is under a screen position, e.g. the mouse.
+    /// None if it's over the ui or past the edge of the map.
+    pub fn screen_to_world(&self, map: &Map, x: i32, y: i32) -> Option<Point> {
+        if x < 0 || x >= VIEW_WIDTH || y < 0 || y >= VIEW_HEIGHT {
+            return None;
+        }
+        let world_x = x + self.min_x;
+        let world_y = y + self.min_y;
+        if world_x < 0 || world_x >= map.width || world_y < 0 || world_y >= map.height {
+            return None;
+        }
+        Some(Point::new(world_x, world_y))
+    }
+}
+
+/// draws the map and everything visible on it around the player
+pub fn render_camera(ecs: &World, ctx: &mut BTerm) {
+    let map = ecs.fetch::<Map>();
+    let camera = Camera::from_player(ecs);
+    draw_map(&map, &camera, ctx);
+
+    let positions = ecs.read_storage::<Position>();
+    let renderables = ecs.read_storage::<Renderable>();
+    let hidden = ecs.read_storage::<Hidden>();
+
+    let mut data = (&positions, &renderables, !&hidden)
+        .join()
+        .collect::<Vec<_>>();
+    data.sort_by(|&a, &b| b.1.render_order.cmp(&a.1.render_order));
+
+    for (pos, render, _hidden) in data.iter() {
+        let idx = map.xy_idx(pos.x, pos.y);
+        if !map.visible_tiles[idx] {
+            continue;
+        }
+        if let Some(screen) = camera.world_to_screen(pos.x, pos.y) {
+            ctx.set(screen.x, screen.y, render.fg, render.bg, render.glyph)
+        }
+    }
+}
+
+/// draws a map generation snapshot, centered since there's no player on it yet
+pub fn render_debug_map(map: &Map, ctx: &mut BTerm) {
+    let camera = Camera::centered_on(Point::new(map.width / 2, map.height / 2));
+    draw_map(map, &camera, ctx);
+}
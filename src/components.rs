@@ -5,9 +5,12 @@ use specs::{prelude::*, saveload::ConvertSaveload, saveload::Marker};
 use specs_derive::*;
 use std::convert::Infallible as NoError;
 
+/// A short-lived effect. Particles with a delay stay Hidden until it runs out,
+/// which is how projectiles are played out tile by tile.
 #[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct ParticleLifetime {
     pub lifetime_ms: f32,
+    pub delay_ms: f32,
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
@@ -25,6 +28,10 @@ pub struct Hidden {}
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct MagicMapper {}
 
+/// moves whoever uses it to the targeted tile
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Teleports {}
+
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct ProvidesFood {}
 
@@ -42,10 +49,53 @@ pub struct HungerClock {
     pub duration: i32,
 }
 
-#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum EquipmentSlot {
     Melee,
     Shield,
+    Ranged,
+    Head,
+    Torso,
+    Legs,
+    Hands,
+    Feet,
+    /// rings go here first, and on the second ring finger once this one is taken
+    Ring,
+    SecondRing,
+    Amulet,
+}
+
+impl EquipmentSlot {
+    /// every slot, in the order the equipment screen lists them
+    pub const ALL: [EquipmentSlot; 11] = [
+        EquipmentSlot::Head,
+        EquipmentSlot::Amulet,
+        EquipmentSlot::Torso,
+        EquipmentSlot::Hands,
+        EquipmentSlot::Ring,
+        EquipmentSlot::SecondRing,
+        EquipmentSlot::Legs,
+        EquipmentSlot::Feet,
+        EquipmentSlot::Melee,
+        EquipmentSlot::Shield,
+        EquipmentSlot::Ranged,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EquipmentSlot::Melee => "Weapon",
+            EquipmentSlot::Shield => "Shield",
+            EquipmentSlot::Ranged => "Ranged",
+            EquipmentSlot::Head => "Head",
+            EquipmentSlot::Torso => "Torso",
+            EquipmentSlot::Legs => "Legs",
+            EquipmentSlot::Hands => "Hands",
+            EquipmentSlot::Feet => "Feet",
+            EquipmentSlot::Ring => "Left ring",
+            EquipmentSlot::SecondRing => "Right ring",
+            EquipmentSlot::Amulet => "Amulet",
+        }
+    }
 }
 
 #[derive(Component, ConvertSaveload, Clone, Debug)]
@@ -69,12 +119,131 @@ pub struct Confusion {
     pub turns: i32,
 }
 
+/// The lasting conditions a creature can be under
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+pub enum StatusKind {
+    Confused,
+    Poisoned,
+    Slowed,
+    Hasted,
+    Paralysed,
+    Regenerating,
+}
+
+impl StatusKind {
+    /// how the status reads in the log and the ui, e.g. "You are poisoned!"
+    pub fn adjective(&self) -> &'static str {
+        match self {
+            StatusKind::Confused => "confused",
+            StatusKind::Poisoned => "poisoned",
+            StatusKind::Slowed => "slowed",
+            StatusKind::Hasted => "hasted",
+            StatusKind::Paralysed => "paralysed",
+            StatusKind::Regenerating => "regenerating",
+        }
+    }
+}
+
+/// One status and how long it has left. Potency is the damage or healing per turn
+/// for poison and regeneration, and unused by the rest.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub turns: i32,
+    pub potency: i32,
+}
+
+/// Every status a creature is under, ticked down each of its turns by the StatusSystem
+#[derive(Component, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.effects.iter().any(|effect| effect.kind == kind)
+    }
+
+    /// Poison doses add up, everything else keeps whichever duration is longer.
+    /// Haste and slow cancel each other out.
+    pub fn add(&mut self, kind: StatusKind, turns: i32, potency: i32) {
+        let opposite = match kind {
+            StatusKind::Hasted => Some(StatusKind::Slowed),
+            StatusKind::Slowed => Some(StatusKind::Hasted),
+            _ => None,
+        };
+        if let Some(opposite) = opposite {
+            if self.has(opposite) {
+                self.effects.retain(|effect| effect.kind != opposite);
+                return;
+            }
+        }
+
+        match self.effects.iter_mut().find(|effect| effect.kind == kind) {
+            Some(existing) => {
+                existing.turns = i32::max(existing.turns, turns);
+                if kind == StatusKind::Poisoned {
+                    existing.potency += potency;
+                } else {
+                    existing.potency = i32::max(existing.potency, potency);
+                }
+            }
+            None => self.effects.push(StatusEffect {
+                kind,
+                turns,
+                potency,
+            }),
+        }
+    }
+
+    /// paralysed creatures still get their turns, they just can't do anything with them
+    pub fn loses_turn(&self) -> bool {
+        self.has(StatusKind::Paralysed)
+    }
+
+    /// haste doubles speed and slow halves it
+    pub fn adjust_speed(&self, speed: i32) -> i32 {
+        let mut speed = speed;
+        if self.has(StatusKind::Hasted) {
+            speed *= 2;
+        }
+        if self.has(StatusKind::Slowed) {
+            speed /= 2;
+        }
+        speed
+    }
+}
+
+/// puts a status on whoever the item is used on
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct InflictsStatus {
+    pub kind: StatusKind,
+    pub turns: i32,
+    pub potency: i32,
+}
+
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct Consumable {}
 
+/// how heavy an item is, in pounds
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Weight {
+    pub weight: f32,
+}
+
+/// Identical items of this kind are carried as one entity, quantity of them at a time.
+/// One is split off the stack whenever one is used, dropped or thrown.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Stackable {
+    pub quantity: i32,
+}
+
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Equippable {
     pub slot: EquipmentSlot,
+    /// takes both hands, so can't be used with a shield
+    #[serde(default)]
+    pub two_handed: bool,
 }
 
 #[derive(Component, ConvertSaveload, Clone)]
@@ -104,6 +273,91 @@ pub struct ProvidesHealing {
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct Monster {}
 
+/// the speed of the player and of most monsters
+pub const NORMAL_SPEED: i32 = 10;
+/// what a turn costs. At normal speed that's a turn every other tick,
+/// so something twice as fast acts twice for every one of the player's moves.
+pub const TURN_ENERGY: i32 = 20;
+
+/// How quickly something acts. Energy builds up by speed every tick,
+/// and it gets a turn whenever it has TURN_ENERGY to spend.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Initiative {
+    pub speed: i32,
+    pub energy: i32,
+}
+
+impl Default for Initiative {
+    fn default() -> Self {
+        Initiative {
+            speed: NORMAL_SPEED,
+            energy: 0,
+        }
+    }
+}
+
+/// Marks whoever gets to act this tick. Handed out by the InitiativeSystem
+/// and taken away again at the start of the next one, so it's never saved.
+#[derive(Component, Clone, Debug)]
+pub struct MyTurn {}
+
+/// What a monster is currently doing
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+pub enum AiMode {
+    Idle,
+    Wander,
+    /// after the player, who was last seen at x, y
+    Chase {
+        x: i32,
+        y: i32,
+    },
+    /// heading for where the player was last seen, giving up after turns_left
+    Search {
+        x: i32,
+        y: i32,
+        turns_left: i32,
+    },
+    Flee,
+}
+
+/// A monster's current mode plus the tuning that decides how it changes
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct AiState {
+    pub mode: AiMode,
+    /// wander when there's nothing to do, rather than stand still
+    pub wanders: bool,
+    /// how many turns to keep looking after losing sight of the player
+    pub search_turns: i32,
+    /// run away once hp drops below this fraction of max_hp; 0 never flees
+    pub flee_below: f32,
+    /// picks up healing potions and drinks them when hurt
+    #[serde(default)]
+    pub uses_items: bool,
+}
+
+impl Default for AiState {
+    fn default() -> Self {
+        AiState {
+            mode: AiMode::Idle,
+            wanders: false,
+            search_turns: 5,
+            flee_below: 0.0,
+            uses_items: false,
+        }
+    }
+}
+
+impl AiState {
+    /// the mode to fall back to when there's no player to deal with
+    pub fn resting_mode(&self) -> AiMode {
+        if self.wanders {
+            AiMode::Wander
+        } else {
+            AiMode::Idle
+        }
+    }
+}
+
 #[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct Name {
     pub name: String,
@@ -112,6 +366,15 @@ pub struct Name {
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct Player {}
 
+/// Where an entity was left on a level the player isn't on.
+/// It has no Position until the player comes back.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct OtherLevelPosition {
+    pub x: i32,
+    pub y: i32,
+    pub depth: i32,
+}
+
 #[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct Position {
     pub x: i32,
@@ -123,6 +386,34 @@ pub struct Ranged {
     pub range: i32,
 }
 
+/// A bow or crossbow. Fires ammunition of the matching kind while equipped.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Launcher {
+    pub range: i32,
+    pub ammo: String,
+}
+
+/// Arrows, bolts... anything a launcher can fire
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Ammunition {
+    pub kind: String,
+    pub damage: i32,
+}
+
+/// can be thrown by hand, no launcher needed
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Thrown {
+    pub range: i32,
+    pub damage: i32,
+}
+
+/// subject.wantstoshoot.item at target; the item flies until it hits something
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToShoot {
+    pub item: Entity,
+    pub target: Point,
+}
+
 #[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct Renderable {
     pub glyph: FontCharType,
@@ -136,6 +427,14 @@ pub struct SerializeMe;
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct SerializationHelper {
     pub map: crate::map::Map,
+    #[serde(default)]
+    pub seed: crate::rng::RunSeed,
+    #[serde(default)]
+    pub turn: i32,
+    #[serde(default)]
+    pub dungeon: crate::dungeon::MasterDungeonMap,
+    #[serde(default)]
+    pub magic_items: crate::identification::MagicItemNames,
 }
 
 #[derive(Component, Debug, ConvertSaveload)]
@@ -148,6 +447,31 @@ pub struct WantsToDropItem {
     pub item: Entity,
 }
 
+/// A +N (or -N) bonus rolled onto a piece of equipment when it spawned.
+/// It's already added into the item's power or defense bonus.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Enchantment {
+    pub bonus: i32,
+}
+
+/// can't be taken off once worn
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Cursed {}
+
+/// equipment the player hasn't worn yet, so doesn't know the enchantment or curse of
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct UnknownEnchantment {}
+
+/// identifies everything in the user's pack
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Identifies {}
+
+/// takes off something equipped and puts it back in the pack
+#[derive(Component, Debug, ConvertSaveload)]
+pub struct WantsToRemoveItem {
+    pub item: Entity,
+}
+
 // subject.wantstomelee.target
 #[derive(Component, Debug, ConvertSaveload)]
 pub struct WantsToMelee {
@@ -165,29 +489,29 @@ pub struct MeleePowerBonus {
     pub power: i32,
 }
 
-#[derive(Component, Debug, Serialize, Deserialize, Clone)]
-pub struct DefenseBonus {
-    pub defense: i32,
+/// Character level and the XP earned towards the next one
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Experience {
+    pub level: i32,
+    pub xp: i32,
 }
 
-#[derive(Component, ConvertSaveload, Clone, Debug)]
-pub struct SufferDamage {
-    pub amount: Vec<i32>,
+impl Experience {
+    /// total XP needed to reach the next level: 100, 300, 600, 1000...
+    pub fn next_level_xp(&self) -> i32 {
+        50 * self.level * (self.level + 1)
+    }
 }
 
-impl SufferDamage {
-    /// if the entity has suffered damage this turn, push new damage into amount vector.
-    /// if the entity has not suffered damage, generate new damage vector, apply damage, and store vector in writestorage
-    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32) {
-        if let Some(suffering) = store.get_mut(victim) {
-            suffering.amount.push(amount);
-        } else {
-            let dmg = SufferDamage {
-                amount: vec![amount],
-            };
-            store.insert(victim, dmg).expect("Unable to insert damage");
-        }
-    }
+/// XP given to whoever lands the killing blow
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct ProvidesXp {
+    pub xp: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct DefenseBonus {
+    pub defense: i32,
 }
 
 // Viewshed stores all tiles visible to the owner before pushing them to the owner
@@ -0,0 +1,21 @@
+// module for where player preferences live on disk
+use std::path::PathBuf;
+
+/// The per-user config directory, next to where the platform keeps other programs' settings.
+/// Falls back to ./config when the platform doesn't say where that is.
+pub fn config_dir() -> PathBuf {
+    let base = if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library").join("Preferences"))
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    match base {
+        Some(base) => base.join("khazad-dum"),
+        None => PathBuf::from("./config"),
+    }
+}
@@ -0,0 +1,195 @@
+// module for the examine panel's descriptions, built from whatever components an entity has
+use crate::{
+    components::*,
+    identification::{display_name, MagicItemNames},
+};
+use specs::prelude::*;
+
+/// Everything the player can tell about an entity, one line per fact.
+/// Items and monsters are described from their components, so new ones need no extra work.
+pub fn describe(ecs: &World, entity: Entity) -> Vec<String> {
+    let mut lines = Vec::new();
+    if ecs.read_storage::<Item>().get(entity).is_some() {
+        describe_item(ecs, entity, &mut lines);
+    } else if ecs.read_storage::<CombatStats>().get(entity).is_some() {
+        describe_creature(ecs, entity, &mut lines);
+    } else if ecs.read_storage::<EntryTrigger>().get(entity).is_some() {
+        lines.push("A trap.".to_string());
+        if let Some(damage) = ecs.read_storage::<InflictsDamage>().get(entity) {
+            lines.push(format!(
+                "Deals {} damage to whoever steps on it.",
+                damage.damage
+            ));
+        }
+    }
+    lines
+}
+
+fn describe_item(ecs: &World, item: Entity, lines: &mut Vec<String>) {
+    if let Some(weight) = ecs.read_storage::<Weight>().get(item) {
+        lines.push(format!("Weighs {:.1} lbs.", weight.weight));
+    }
+
+    // an unidentified scroll or potion gives nothing away
+    let known = ecs.read_storage::<Name>().get(item).map_or(true, |name| {
+        ecs.fetch::<MagicItemNames>().is_identified(&name.name)
+    });
+    if !known {
+        lines.push("You don't know what this does.".to_string());
+        return;
+    }
+
+    if let Some(equippable) = ecs.read_storage::<Equippable>().get(item) {
+        let mut slot = format!("Worn: {}", equippable.slot.name());
+        if equippable.two_handed {
+            slot += " (two-handed)";
+        }
+        lines.push(slot);
+    }
+    // until it's worn, an item shows its bonuses without the enchantment
+    let hidden_bonus = match (
+        ecs.read_storage::<UnknownEnchantment>().get(item),
+        ecs.read_storage::<Enchantment>().get(item),
+    ) {
+        (Some(_), Some(enchantment)) => enchantment.bonus,
+        _ => 0,
+    };
+    if let Some(power) = ecs.read_storage::<MeleePowerBonus>().get(item) {
+        lines.push(format!("Power {:+}", power.power - hidden_bonus));
+    }
+    if let Some(defense) = ecs.read_storage::<DefenseBonus>().get(item) {
+        let bonus = match ecs.read_storage::<MeleePowerBonus>().get(item) {
+            Some(_) => defense.defense,
+            None => defense.defense - hidden_bonus,
+        };
+        lines.push(format!("Defense {:+}", bonus));
+    }
+    if ecs.read_storage::<UnknownEnchantment>().get(item).is_some() {
+        lines.push("Its enchantment is unknown until worn.".to_string());
+    } else if ecs.read_storage::<Cursed>().get(item).is_some() {
+        lines.push("Cursed: it won't come off once worn.".to_string());
+    }
+
+    if let Some(damage) = ecs.read_storage::<InflictsDamage>().get(item) {
+        lines.push(format!("Damage: {}", damage.damage));
+    }
+    if let Some(ranged) = ecs.read_storage::<Ranged>().get(item) {
+        lines.push(format!("Range: {}", ranged.range));
+    }
+    if let Some(aoe) = ecs.read_storage::<AreaOfEffect>().get(item) {
+        lines.push(format!("Blast radius: {}", aoe.radius));
+    }
+    if let Some(healing) = ecs.read_storage::<ProvidesHealing>().get(item) {
+        lines.push(format!("Heals {} hit points.", healing.restore_hp));
+    }
+    if let Some(confusion) = ecs.read_storage::<Confusion>().get(item) {
+        lines.push(format!("Confuses for {} turns.", confusion.turns));
+    }
+    if let Some(status) = ecs.read_storage::<InflictsStatus>().get(item) {
+        lines.push(format!(
+            "Leaves the target {} for {} turns.",
+            status.kind.adjective(),
+            status.turns
+        ));
+    }
+    if ecs.read_storage::<Teleports>().get(item).is_some() {
+        lines.push("Teleports the user to the target.".to_string());
+    }
+    if ecs.read_storage::<MagicMapper>().get(item).is_some() {
+        lines.push("Reveals the whole level.".to_string());
+    }
+    if ecs.read_storage::<Identifies>().get(item).is_some() {
+        lines.push("Identifies everything you carry.".to_string());
+    }
+    if ecs.read_storage::<ProvidesFood>().get(item).is_some() {
+        lines.push("Food: staves off hunger.".to_string());
+    }
+    if let Some(launcher) = ecs.read_storage::<Launcher>().get(item) {
+        lines.push(format!(
+            "Fires {}s up to {} tiles.",
+            launcher.ammo, launcher.range
+        ));
+    }
+    if let Some(ammo) = ecs.read_storage::<Ammunition>().get(item) {
+        lines.push(format!(
+            "Ammunition ({}), damage {}.",
+            ammo.kind, ammo.damage
+        ));
+    }
+    if let Some(thrown) = ecs.read_storage::<Thrown>().get(item) {
+        lines.push(format!(
+            "Can be thrown {} tiles for {} damage.",
+            thrown.range, thrown.damage
+        ));
+    }
+    if ecs.read_storage::<Consumable>().get(item).is_some() {
+        lines.push("Used up after one use.".to_string());
+    }
+}
+
+fn describe_creature(ecs: &World, creature: Entity, lines: &mut Vec<String>) {
+    if creature == *ecs.fetch::<Entity>() {
+        lines.push("That's you.".to_string());
+    }
+
+    if let Some(stats) = ecs.read_storage::<CombatStats>().get(creature) {
+        let health = stats.hp as f32 / stats.max_hp as f32;
+        let state = if health >= 1.0 {
+            "Unhurt"
+        } else if health > 0.66 {
+            "Lightly wounded"
+        } else if health > 0.33 {
+            "Badly wounded"
+        } else {
+            "Near death"
+        };
+        lines.push(state.to_string());
+    }
+
+    if let Some(ai) = ecs.read_storage::<AiState>().get(creature) {
+        let attitude = match ai.mode {
+            AiMode::Idle | AiMode::Wander => "Hostile, but hasn't noticed you.",
+            AiMode::Chase { .. } => "Hostile, and coming for you!",
+            AiMode::Search { .. } => "Hostile, and looking for you.",
+            AiMode::Flee => "Hostile, but running scared.",
+        };
+        lines.push(attitude.to_string());
+    } else if ecs.read_storage::<Monster>().get(creature).is_some() {
+        lines.push("Hostile.".to_string());
+    }
+
+    if let Some(initiative) = ecs.read_storage::<Initiative>().get(creature) {
+        if initiative.speed > NORMAL_SPEED {
+            lines.push("Moves quickly.".to_string());
+        } else if initiative.speed < NORMAL_SPEED {
+            lines.push("Moves slowly.".to_string());
+        }
+    }
+
+    if let Some(status) = ecs.read_storage::<StatusEffects>().get(creature) {
+        for effect in status.effects.iter() {
+            lines.push(format!(
+                "{} ({} turns)",
+                capitalize(effect.kind.adjective()),
+                effect.turns
+            ));
+        }
+    }
+
+    // what it's wielding and wearing
+    let equipped = ecs.read_storage::<Equipped>();
+    let entities = ecs.entities();
+    for (item, worn) in (&entities, &equipped).join() {
+        if worn.owner == creature {
+            lines.push(format!("{}: {}", worn.slot.name(), display_name(ecs, item)));
+        }
+    }
+}
+
+pub fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,86 @@
+// module for the levels the player has already been to
+use crate::{
+    components::{OtherLevelPosition, ParticleLifetime, Player, Position},
+    map::Map,
+};
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+use std::collections::HashMap;
+
+/// Every level the player has left, by depth, so going back finds it as it was left
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct MasterDungeonMap {
+    maps: HashMap<i32, Map>,
+}
+
+impl MasterDungeonMap {
+    /// remembers a level, replacing what was stored for its depth
+    pub fn store_map(&mut self, map: &Map) {
+        self.maps.insert(map.depth, map.clone());
+    }
+
+    /// a copy of the stored level, ready to become the active map. None if it's never been visited
+    pub fn get_map(&self, depth: i32) -> Option<Map> {
+        self.maps.get(&depth).map(|map| {
+            let mut map = map.clone();
+            map.tile_content = vec![Vec::new(); map.tiles.len()];
+            map
+        })
+    }
+}
+
+/// Takes everything off the current level, remembering where it was.
+/// Particles are only for show and are deleted instead.
+pub fn freeze_level_entities(ecs: &mut World) {
+    let depth = ecs.fetch::<Map>().depth;
+    let mut to_freeze: Vec<(Entity, i32, i32)> = Vec::new();
+    let mut to_delete: Vec<Entity> = Vec::new();
+    {
+        let entities = ecs.entities();
+        let positions = ecs.read_storage::<Position>();
+        let players = ecs.read_storage::<Player>();
+        let particles = ecs.read_storage::<ParticleLifetime>();
+        for (entity, pos, _player) in (&entities, &positions, !&players).join() {
+            if particles.get(entity).is_some() {
+                to_delete.push(entity);
+            } else {
+                to_freeze.push((entity, pos.x, pos.y));
+            }
+        }
+    }
+
+    {
+        let mut positions = ecs.write_storage::<Position>();
+        let mut other_level = ecs.write_storage::<OtherLevelPosition>();
+        for (entity, x, y) in to_freeze {
+            positions.remove(entity);
+            other_level
+                .insert(entity, OtherLevelPosition { x, y, depth })
+                .expect("Unable to freeze entity");
+        }
+    }
+    for entity in to_delete {
+        ecs.delete_entity(entity).expect("Unable to delete entity");
+    }
+}
+
+/// puts back everything that was left on the current level
+pub fn thaw_level_entities(ecs: &mut World) {
+    let depth = ecs.fetch::<Map>().depth;
+    let entities = ecs.entities();
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level = ecs.write_storage::<OtherLevelPosition>();
+
+    let mut to_thaw: Vec<Entity> = Vec::new();
+    for (entity, pos) in (&entities, &other_level).join() {
+        if pos.depth == depth {
+            positions
+                .insert(entity, Position { x: pos.x, y: pos.y })
+                .expect("Unable to thaw entity");
+            to_thaw.push(entity);
+        }
+    }
+    for entity in to_thaw {
+        other_level.remove(entity);
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{
+    components::{CombatStats, Experience, Player, Position, ProvidesXp},
+    gamelog::{GameLog, LogCategory},
+    map::Map,
+};
+use bracket_lib::prelude::{GOLD, RGB};
+use specs::prelude::*;
+
+/// Takes hit points and leaves a bloodstain. The blow that takes the last hit point
+/// earns the creator the target's XP.
+pub fn inflict_damage(ecs: &mut World, creator: Option<Entity>, target: Entity, amount: i32) {
+    let killed = {
+        let mut stats = ecs.write_storage::<CombatStats>();
+        let stats = match stats.get_mut(target) {
+            Some(stats) => stats,
+            None => return,
+        };
+        let was_alive = stats.hp > 0;
+        stats.hp -= amount;
+        was_alive && stats.hp < 1
+    };
+
+    if let Some(pos) = ecs.read_storage::<Position>().get(target) {
+        let mut map = ecs.write_resource::<Map>();
+        let idx = map.xy_idx(pos.x, pos.y);
+        map.bloodstains.insert(idx);
+    }
+
+    if killed {
+        let reward = ecs.read_storage::<ProvidesXp>().get(target).map(|r| r.xp);
+        if let (Some(killer), Some(xp)) = (creator, reward) {
+            award_xp(ecs, killer, xp);
+        }
+    }
+}
+
+pub fn heal_damage(ecs: &mut World, target: Entity, amount: i32) {
+    let mut stats = ecs.write_storage::<CombatStats>();
+    if let Some(stats) = stats.get_mut(target) {
+        stats.hp = i32::min(stats.max_hp, stats.hp + amount);
+    }
+}
+
+fn award_xp(ecs: &mut World, killer: Entity, xp: i32) {
+    let mut experience = ecs.write_storage::<Experience>();
+    let mut stats = ecs.write_storage::<CombatStats>();
+    let players = ecs.read_storage::<Player>();
+    let mut log = ecs.write_resource::<GameLog>();
+
+    if let Some(exp) = experience.get_mut(killer) {
+        exp.xp += xp;
+        while exp.xp >= exp.next_level_xp() {
+            exp.level += 1;
+            if let Some(stats) = stats.get_mut(killer) {
+                level_up(stats, exp.level);
+            }
+            if players.get(killer).is_some() {
+                log.add_colored(
+                    LogCategory::General,
+                    format!("Welcome to level {}! You feel stronger.", exp.level),
+                    RGB::named(GOLD),
+                );
+            }
+        }
+    }
+}
+
+/// +8 max hp (and the hp to go with it) and +1 power every level, +1 defense every other level
+fn level_up(stats: &mut CombatStats, new_level: i32) {
+    stats.max_hp += 8;
+    stats.hp += 8;
+    stats.power += 1;
+    if new_level % 2 == 0 {
+        stats.defense += 1;
+    }
+}
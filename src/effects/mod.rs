@@ -0,0 +1,202 @@
+// mod.rs for the effects queue
+mod damage;
+mod movement;
+mod particles;
+mod status;
+mod triggers;
+
+use crate::{
+    components::{Position, StatusKind},
+    map::Map,
+};
+use bracket_lib::prelude::*;
+use specs::prelude::*;
+use std::collections::VecDeque;
+
+/// Something that happens to whatever it's aimed at
+#[derive(Clone, Debug)]
+pub enum EffectType {
+    Damage {
+        amount: i32,
+    },
+    Healing {
+        amount: i32,
+    },
+    Status {
+        kind: StatusKind,
+        turns: i32,
+        potency: i32,
+    },
+    WellFed,
+    Particle {
+        glyph: FontCharType,
+        fg: RGB,
+        bg: RGB,
+        lifetime: f32,
+    },
+    TeleportTo {
+        x: i32,
+        y: i32,
+    },
+    /// expands into the effects of every component the item has
+    ItemUse {
+        item: Entity,
+    },
+}
+
+impl EffectType {
+    /// particles only ever land on tiles, everything else hits what's standing on them
+    fn affects_entities(&self) -> bool {
+        !matches!(self, EffectType::Particle { .. })
+    }
+}
+
+/// What an effect is aimed at
+#[derive(Clone, Debug)]
+pub enum Targets {
+    Single {
+        target: Entity,
+    },
+    Tile {
+        tile_idx: usize,
+    },
+    Tiles {
+        tiles: Vec<usize>,
+    },
+    /// every tile in sight of center within radius
+    Area {
+        center: Point,
+        radius: i32,
+    },
+}
+
+/// One queued effect. creator is whoever caused it, and gets the credit for any kills.
+pub struct EffectSpawner {
+    pub creator: Option<Entity>,
+    pub effect_type: EffectType,
+    pub targets: Targets,
+}
+
+/// Effects waiting to be applied. Systems push onto it, run_effects_queue applies them in order.
+#[derive(Default)]
+pub struct EffectQueue {
+    queue: VecDeque<EffectSpawner>,
+}
+
+impl EffectQueue {
+    pub fn new() -> EffectQueue {
+        EffectQueue {
+            queue: VecDeque::new(),
+        }
+    }
+
+    pub fn add_effect(
+        &mut self,
+        creator: Option<Entity>,
+        effect_type: EffectType,
+        targets: Targets,
+    ) {
+        self.queue.push_back(EffectSpawner {
+            creator,
+            effect_type,
+            targets,
+        });
+    }
+}
+
+/// applies every queued effect, oldest first
+pub fn run_effects_queue(ecs: &mut World) {
+    loop {
+        let effect = ecs.write_resource::<EffectQueue>().queue.pop_front();
+        match effect {
+            Some(effect) => target_applicator(ecs, &effect),
+            None => break,
+        }
+    }
+}
+
+fn target_applicator(ecs: &mut World, effect: &EffectSpawner) {
+    if let EffectType::ItemUse { item } = effect.effect_type {
+        triggers::item_trigger(ecs, effect.creator, item, &effect.targets);
+        return;
+    }
+
+    match &effect.targets {
+        Targets::Single { target } => affect_entity(ecs, effect, *target),
+        Targets::Tile { tile_idx } => affect_tile(ecs, effect, *tile_idx),
+        Targets::Tiles { tiles } => {
+            for tile_idx in tiles.iter() {
+                affect_tile(ecs, effect, *tile_idx);
+            }
+        }
+        Targets::Area { center, radius } => {
+            for tile_idx in area_tiles(&ecs.fetch::<Map>(), *center, *radius) {
+                affect_tile(ecs, effect, tile_idx);
+            }
+        }
+    }
+}
+
+/// the tiles an area covers, leaving out the map's outer wall
+fn area_tiles(map: &Map, center: Point, radius: i32) -> Vec<usize> {
+    field_of_view(center, radius, map)
+        .into_iter()
+        .filter(|p| p.x > 0 && p.x < map.width - 1 && p.y > 0 && p.y < map.height - 1)
+        .map(|p| map.xy_idx(p.x, p.y))
+        .collect()
+}
+
+/// the entities standing on the targeted tiles, or the single target
+fn entities_in_targets(ecs: &World, targets: &Targets) -> Vec<Entity> {
+    let map = ecs.fetch::<Map>();
+    let tiles = match targets {
+        Targets::Single { target } => return vec![*target],
+        Targets::Tile { tile_idx } => vec![*tile_idx],
+        Targets::Tiles { tiles } => tiles.clone(),
+        Targets::Area { center, radius } => area_tiles(&map, *center, *radius),
+    };
+    tiles
+        .iter()
+        .flat_map(|idx| map.tile_content[*idx].iter().copied())
+        .collect()
+}
+
+fn affect_tile(ecs: &mut World, effect: &EffectSpawner, tile_idx: usize) {
+    if let EffectType::Particle { .. } = effect.effect_type {
+        particles::particle_to_tile(ecs, tile_idx, &effect.effect_type);
+    }
+    if effect.effect_type.affects_entities() {
+        let content = ecs.fetch::<Map>().tile_content[tile_idx].clone();
+        for entity in content {
+            affect_entity(ecs, effect, entity);
+        }
+    }
+}
+
+fn affect_entity(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    match &effect.effect_type {
+        EffectType::Damage { amount } => {
+            damage::inflict_damage(ecs, effect.creator, target, *amount)
+        }
+        EffectType::Healing { amount } => damage::heal_damage(ecs, target, *amount),
+        EffectType::Status {
+            kind,
+            turns,
+            potency,
+        } => status::add_status(ecs, target, *kind, *turns, *potency),
+        EffectType::WellFed => status::well_fed(ecs, target),
+        EffectType::Particle { .. } => {
+            let tile_idx = {
+                let map = ecs.fetch::<Map>();
+                ecs.read_storage::<Position>()
+                    .get(target)
+                    .map(|pos| map.xy_idx(pos.x, pos.y))
+            };
+            if let Some(tile_idx) = tile_idx {
+                particles::particle_to_tile(ecs, tile_idx, &effect.effect_type);
+            }
+        }
+        EffectType::TeleportTo { x, y } => movement::apply_teleport(ecs, target, *x, *y),
+        EffectType::ItemUse { .. } => {}
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{
+    components::{EntityMoved, Position, Viewshed},
+    gamelog::{GameLog, LogCategory},
+    map::Map,
+};
+use bracket_lib::prelude::Point;
+use specs::prelude::*;
+
+/// Moves the target straight to x, y, unless something is already standing there
+pub fn apply_teleport(ecs: &mut World, target: Entity, x: i32, y: i32) {
+    let player_entity = *ecs.fetch::<Entity>();
+    {
+        let map = ecs.fetch::<Map>();
+        if x < 0 || x >= map.width || y < 0 || y >= map.height || map.blocked[map.xy_idx(x, y)] {
+            if target == player_entity {
+                ecs.write_resource::<GameLog>().add(
+                    LogCategory::Item,
+                    "Something blocks the way, and the magic fizzles.".to_string(),
+                );
+            }
+            return;
+        }
+    }
+
+    let mut positions = ecs.write_storage::<Position>();
+    let pos = match positions.get_mut(target) {
+        Some(pos) => pos,
+        None => return,
+    };
+    pos.x = x;
+    pos.y = y;
+    if target == player_entity {
+        *ecs.write_resource::<Point>() = Point::new(x, y);
+    }
+    if let Some(viewshed) = ecs.write_storage::<Viewshed>().get_mut(target) {
+        viewshed.dirty = true;
+    }
+    // landing on a trap sets it off like walking onto it would
+    ecs.write_storage::<EntityMoved>()
+        .insert(target, EntityMoved {})
+        .expect("Unable to insert marker");
+}
@@ -0,0 +1,24 @@
+use super::EffectType;
+use crate::{map::Map, systems::particle_system::ParticleBuilder};
+use specs::prelude::*;
+
+pub fn particle_to_tile(ecs: &mut World, tile_idx: usize, effect: &EffectType) {
+    if let EffectType::Particle {
+        glyph,
+        fg,
+        bg,
+        lifetime,
+    } = effect
+    {
+        let map = ecs.fetch::<Map>();
+        let mut particle_builder = ecs.write_resource::<ParticleBuilder>();
+        particle_builder.request(
+            tile_idx as i32 % map.width,
+            tile_idx as i32 / map.width,
+            *fg,
+            *bg,
+            *glyph,
+            *lifetime,
+        );
+    }
+}
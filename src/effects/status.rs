@@ -0,0 +1,28 @@
+use crate::components::{CombatStats, HungerClock, HungerState, StatusEffects, StatusKind};
+use specs::prelude::*;
+
+/// Puts the status on the target, stacking with anything it's already under
+pub fn add_status(ecs: &mut World, target: Entity, kind: StatusKind, turns: i32, potency: i32) {
+    // items and props on the same tile have nothing to afflict
+    if ecs.read_storage::<CombatStats>().get(target).is_none() {
+        return;
+    }
+    let mut statuses = ecs.write_storage::<StatusEffects>();
+    match statuses.get_mut(target) {
+        Some(existing) => existing.add(kind, turns, potency),
+        None => {
+            let mut fresh = StatusEffects::default();
+            fresh.add(kind, turns, potency);
+            statuses
+                .insert(target, fresh)
+                .expect("Unable to insert status");
+        }
+    }
+}
+
+pub fn well_fed(ecs: &mut World, target: Entity) {
+    if let Some(clock) = ecs.write_storage::<HungerClock>().get_mut(target) {
+        clock.state = HungerState::WellFed;
+        clock.duration = 20;
+    }
+}
@@ -0,0 +1,354 @@
+use super::{entities_in_targets, target_applicator, EffectSpawner, EffectType, Targets};
+use crate::{
+    components::*,
+    gamelog::{GameLog, LogCategory},
+    identification::MagicItemNames,
+    map::Map,
+    RunState,
+};
+use bracket_lib::prelude::*;
+use specs::prelude::*;
+
+/// Turns each of an item's components into the matching effect on the targets,
+/// then uses the item up if it's consumable
+pub fn item_trigger(ecs: &mut World, creator: Option<Entity>, item: Entity, targets: &Targets) {
+    // using something is the surest way to find out what it is
+    if creator == Some(*ecs.fetch::<Entity>()) {
+        identify_kind(ecs, item);
+    }
+    if let (Some(user), true) = (
+        creator,
+        ecs.read_storage::<Identifies>().get(item).is_some(),
+    ) {
+        identify_pack(ecs, user);
+    }
+    let effects = item_effects(ecs, creator, item, targets);
+    for (effect_type, targets) in effects {
+        target_applicator(
+            ecs,
+            &EffectSpawner {
+                creator,
+                effect_type,
+                targets,
+            },
+        );
+    }
+
+    if ecs.read_storage::<Consumable>().get(item).is_some() {
+        use_up(ecs, item);
+    }
+}
+
+/// takes one off a stack, or deletes the item if it's the last one
+fn use_up(ecs: &mut World, item: Entity) {
+    if let Some(stack) = ecs.write_storage::<Stackable>().get_mut(item) {
+        if stack.quantity > 1 {
+            stack.quantity -= 1;
+            return;
+        }
+    }
+    ecs.delete_entity(item).expect("Delete failed");
+}
+
+/// works out what using the item does, logging it as it goes
+fn item_effects(
+    ecs: &World,
+    creator: Option<Entity>,
+    item: Entity,
+    targets: &Targets,
+) -> Vec<(EffectType, Targets)> {
+    let player_entity = *ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let combat_stats = ecs.read_storage::<CombatStats>();
+    let hunger_clocks = ecs.read_storage::<HungerClock>();
+    let mut gamelog = ecs.write_resource::<GameLog>();
+
+    let by_player = creator == Some(player_entity);
+    // the player can always see what they do themselves, monsters only when in view
+    let user_visible = by_player
+        || creator
+            .and_then(|user| ecs.read_storage::<Position>().get(user).cloned())
+            .map_or(false, |pos| {
+                let map = ecs.fetch::<Map>();
+                map.visible_tiles[map.xy_idx(pos.x, pos.y)]
+            });
+    let user_name = creator
+        .and_then(|user| names.get(user))
+        .map_or("Something".to_string(), |name| name.name.clone());
+    let item_name = names.get(item).map_or("something".to_string(), |name| {
+        ecs.fetch::<MagicItemNames>().display(&name.name)
+    });
+    // only things with hit points are worth talking about
+    let victims: Vec<Entity> = entities_in_targets(ecs, targets)
+        .into_iter()
+        .filter(|victim| combat_stats.get(*victim).is_some())
+        .collect();
+
+    let mut effects = Vec::new();
+    let mut particle: Option<(char, (u8, u8, u8))> = None;
+
+    if ecs.read_storage::<ProvidesFood>().get(item).is_some() {
+        effects.push((EffectType::WellFed, targets.clone()));
+        if by_player && victims.iter().any(|v| hunger_clocks.get(*v).is_some()) {
+            gamelog.add(LogCategory::Hunger, format!("You eat the {}", item_name));
+        }
+    }
+
+    if ecs.read_storage::<MagicMapper>().get(item).is_some() {
+        *ecs.write_resource::<RunState>() = RunState::MagicMapReveal { row: 0 };
+        gamelog.add(
+            LogCategory::Discovery,
+            "The map is revealed to you!".to_string(),
+        );
+    }
+
+    if let Some(healer) = ecs.read_storage::<ProvidesHealing>().get(item) {
+        effects.push((
+            EffectType::Healing {
+                amount: healer.restore_hp,
+            },
+            targets.clone(),
+        ));
+        particle = Some(('♥', YELLOW));
+        if by_player {
+            gamelog.add(
+                LogCategory::Item,
+                format!(
+                    "You used the {}, healing {} hit points.",
+                    item_name, healer.restore_hp
+                ),
+            );
+        } else if user_visible {
+            gamelog.add(
+                LogCategory::Item,
+                format!("{} quaffs the {}.", user_name, item_name),
+            );
+        }
+    }
+
+    // confusion items predate the general status components, and still work the same way
+    let status = ecs
+        .read_storage::<InflictsStatus>()
+        .get(item)
+        .cloned()
+        .or_else(|| {
+            ecs.read_storage::<Confusion>()
+                .get(item)
+                .map(|confusion| InflictsStatus {
+                    kind: StatusKind::Confused,
+                    turns: confusion.turns,
+                    potency: 0,
+                })
+        });
+    if let Some(status) = status {
+        effects.push((
+            EffectType::Status {
+                kind: status.kind,
+                turns: status.turns,
+                potency: status.potency,
+            },
+            targets.clone(),
+        ));
+        particle = Some(match status.kind {
+            StatusKind::Confused => ('?', RED),
+            StatusKind::Poisoned => ('!', GREEN),
+            StatusKind::Slowed | StatusKind::Paralysed => ('~', CYAN),
+            StatusKind::Hasted => ('»', YELLOW),
+            StatusKind::Regenerating => ('♥', GREEN),
+        });
+        let adjective = status.kind.adjective();
+        for victim in victims.iter() {
+            if *victim == player_entity {
+                if !by_player {
+                    gamelog.add(
+                        LogCategory::Combat,
+                        format!("{} uses {} on you.", user_name, item_name),
+                    );
+                }
+                gamelog.add(LogCategory::Combat, format!("You are {}!", adjective));
+            } else if by_player {
+                if let Some(victim_name) = names.get(*victim) {
+                    gamelog.add(
+                        LogCategory::Combat,
+                        format!(
+                            "You use {} on {}, leaving them {}.",
+                            item_name, victim_name.name, adjective
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    if let Some(damage) = ecs.read_storage::<InflictsDamage>().get(item) {
+        effects.push((
+            EffectType::Damage {
+                amount: damage.damage,
+            },
+            targets.clone(),
+        ));
+        particle = Some(('‼', RED));
+        for victim in victims.iter() {
+            if by_player {
+                if let Some(victim_name) = names.get(*victim) {
+                    gamelog.add(
+                        LogCategory::Combat,
+                        format!(
+                            "You use the {} on {}, inflicting {} damage.",
+                            item_name, victim_name.name, damage.damage
+                        ),
+                    );
+                }
+            } else if *victim == player_entity {
+                gamelog.add(
+                    LogCategory::Combat,
+                    format!(
+                        "{} uses {} on you, inflicting {} damage.",
+                        user_name, item_name, damage.damage
+                    ),
+                );
+            }
+        }
+    }
+
+    if ecs.read_storage::<Teleports>().get(item).is_some() {
+        if let (Some(user), Targets::Tile { tile_idx }) = (creator, targets) {
+            let width = ecs.fetch::<Map>().width;
+            effects.push((
+                EffectType::TeleportTo {
+                    x: *tile_idx as i32 % width,
+                    y: *tile_idx as i32 / width,
+                },
+                Targets::Single { target: user },
+            ));
+            particle = Some(('*', MAGENTA));
+        }
+    }
+
+    // a blast shows across its whole area, anything else where it lands
+    if let Targets::Area { .. } = targets {
+        particle = Some(('░', ORANGE));
+    }
+    if let Some((glyph, fg)) = particle {
+        effects.push((
+            EffectType::Particle {
+                glyph: to_cp437(glyph),
+                fg: RGB::named(fg),
+                bg: RGB::named(BLACK),
+                lifetime: 200.0,
+            },
+            targets.clone(),
+        ));
+    }
+
+    effects
+}
+
+/// Learns what every item of this kind is, telling the player if it's news to them
+fn identify_kind(ecs: &World, item: Entity) -> bool {
+    let real = match ecs.read_storage::<Name>().get(item) {
+        Some(name) => name.name.clone(),
+        None => return false,
+    };
+    let mut magic_items = ecs.write_resource::<MagicItemNames>();
+    let made_up = magic_items.display(&real);
+    if !magic_items.identify(&real) {
+        return false;
+    }
+    ecs.write_resource::<GameLog>()
+        .add(LogCategory::Item, format!("The {} is a {}.", made_up, real));
+    true
+}
+
+/// identifies the kind and the enchantment of everything the owner carries or wears
+fn identify_pack(ecs: &World, owner: Entity) {
+    let entities = ecs.entities();
+    let carried: Vec<Entity> = {
+        let backpack = ecs.read_storage::<InBackpack>();
+        let equipped = ecs.read_storage::<Equipped>();
+        (&entities)
+            .join()
+            .filter(|item| {
+                backpack
+                    .get(*item)
+                    .map_or(false, |pack| pack.owner == owner)
+                    || equipped
+                        .get(*item)
+                        .map_or(false, |worn| worn.owner == owner)
+            })
+            .collect()
+    };
+
+    let mut learned = false;
+    for item in carried {
+        learned |= identify_kind(ecs, item);
+        learned |= ecs
+            .write_storage::<UnknownEnchantment>()
+            .remove(item)
+            .is_some();
+    }
+    let message = if learned {
+        "You know your belongings inside and out."
+    } else {
+        "You learn nothing new about your belongings."
+    };
+    ecs.write_resource::<GameLog>()
+        .add(LogCategory::Item, message.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game_world::GameWorld, rng::RunSeed};
+
+    #[test]
+    fn a_monster_drinks_one_potion_from_a_stack() {
+        let mut world = GameWorld::new(RunSeed::new(1));
+        let ecs = &mut world.ecs;
+        let monster = ecs
+            .create_entity()
+            .with(Monster {})
+            .with(Name {
+                name: "Goblin".to_string(),
+            })
+            .with(CombatStats {
+                max_hp: 10,
+                hp: 2,
+                defense: 0,
+                power: 3,
+            })
+            .build();
+        let potions = ecs
+            .create_entity()
+            .with(Item {})
+            .with(Consumable {})
+            .with(Name {
+                name: "Health Potion".to_string(),
+            })
+            .with(ProvidesHealing { restore_hp: 8 })
+            .with(Stackable { quantity: 2 })
+            .with(InBackpack { owner: monster })
+            .build();
+
+        item_trigger(
+            ecs,
+            Some(monster),
+            potions,
+            &Targets::Single { target: monster },
+        );
+        ecs.maintain();
+
+        assert!(ecs.is_alive(potions));
+        assert_eq!(
+            ecs.read_storage::<Stackable>()
+                .get(potions)
+                .unwrap()
+                .quantity,
+            1
+        );
+        assert_eq!(
+            ecs.read_storage::<CombatStats>().get(monster).unwrap().hp,
+            10
+        );
+    }
+}
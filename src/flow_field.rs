@@ -0,0 +1,147 @@
+// module for the shared monster pathing maps
+use crate::map::{Map, TileType};
+use bracket_lib::prelude::Point;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// how much harder the flee map pulls than the chase map; above 1 makes monsters
+/// prefer running past the player to open space over cowering in a dead end
+const FLEE_WEIGHT: f32 = -1.2;
+
+/// Distance maps every monster reads from instead of pathing on its own.
+/// Built from the walls only, so monsters moving about never invalidate them;
+/// they're rebuilt when the player moves or the level changes.
+#[derive(Default)]
+pub struct FlowField {
+    /// cost of walking from each tile to the player, f32::MAX where unreachable
+    pub toward: Vec<f32>,
+    /// lower is further from the player, following it downhill runs away
+    pub away: Vec<f32>,
+    player_idx: Option<usize>,
+    depth: i32,
+}
+
+impl FlowField {
+    /// rebuilds both maps if the player has moved since they were last built
+    pub fn update(&mut self, map: &Map, player_pos: Point) {
+        let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+        if self.player_idx == Some(player_idx) && self.depth == map.depth {
+            return;
+        }
+
+        let mut toward = vec![f32::MAX; map.tiles.len()];
+        toward[player_idx] = 0.0;
+        self.toward = dijkstra(map, toward);
+
+        // start from the chase map turned upside down, then let it settle so
+        // dead ends score worse than the open ground behind them
+        let away = self
+            .toward
+            .iter()
+            .map(|cost| {
+                if *cost == f32::MAX {
+                    f32::MAX
+                } else {
+                    *cost * FLEE_WEIGHT
+                }
+            })
+            .collect();
+        self.away = dijkstra(map, away);
+
+        self.player_idx = Some(player_idx);
+        self.depth = map.depth;
+    }
+
+    /// the exit that gets closest to the player, if any gets closer than here
+    pub fn step_toward(&self, here: usize, exits: &[(usize, f32)]) -> Option<usize> {
+        best_exit(&self.toward, here, exits)
+    }
+
+    /// the exit that best gets away from the player, if any is safer than here
+    pub fn step_away(&self, here: usize, exits: &[(usize, f32)]) -> Option<usize> {
+        best_exit(&self.away, here, exits)
+    }
+}
+
+/// Of the given exits, the one furthest downhill on field that improves on here.
+/// None if every exit is uphill.
+fn best_exit(field: &[f32], here: usize, exits: &[(usize, f32)]) -> Option<usize> {
+    exits
+        .iter()
+        .map(|(exit, _cost)| *exit)
+        .filter(|exit| field[*exit] < field[here])
+        .min_by(|a, b| field[*a].partial_cmp(&field[*b]).unwrap_or(Ordering::Equal))
+}
+
+/// a tile waiting to be expanded by a Dijkstra search, cheapest first
+#[derive(PartialEq)]
+pub(crate) struct Frontier {
+    pub(crate) cost: f32,
+    pub(crate) idx: usize,
+}
+
+impl Eq for Frontier {}
+
+impl Ord for Frontier {
+    // reversed, so the BinaryHeap pops the cheapest tile first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| self.idx.cmp(&other.idx))
+    }
+}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Relaxes costs outwards over walkable tiles until nothing can be made cheaper.
+/// Every tile that isn't f32::MAX to begin with is a starting point.
+fn dijkstra(map: &Map, mut costs: Vec<f32>) -> Vec<f32> {
+    let mut frontier: BinaryHeap<Frontier> = costs
+        .iter()
+        .enumerate()
+        .filter(|(_idx, cost)| **cost < f32::MAX)
+        .map(|(idx, cost)| Frontier { cost: *cost, idx })
+        .collect();
+
+    while let Some(Frontier { cost, idx }) = frontier.pop() {
+        if cost > costs[idx] {
+            continue;
+        }
+        let x = idx as i32 % map.width;
+        let y = idx as i32 / map.width;
+        for (dx, dy, step) in [
+            (-1, 0, 1.0),
+            (1, 0, 1.0),
+            (0, -1, 1.0),
+            (0, 1, 1.0),
+            (-1, -1, 1.45),
+            (1, -1, 1.45),
+            (-1, 1, 1.45),
+            (1, 1, 1.45),
+        ] {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || nx >= map.width || ny < 0 || ny >= map.height {
+                continue;
+            }
+            let next = map.xy_idx(nx, ny);
+            if map.tiles[next] == TileType::Wall {
+                continue;
+            }
+            let next_cost = cost + step;
+            if next_cost < costs[next] {
+                costs[next] = next_cost;
+                frontier.push(Frontier {
+                    cost: next_cost,
+                    idx: next,
+                });
+            }
+        }
+    }
+    costs
+}
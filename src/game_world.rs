@@ -0,0 +1,474 @@
+// World setup and turn stepping, shared by the windowed game and headless runs
+use crate::{
+    components::*,
+    dungeon::{freeze_level_entities, thaw_level_entities, MasterDungeonMap},
+    effects::{run_effects_queue, EffectQueue},
+    entities::create_player,
+    flow_field::FlowField,
+    gamelog::{GameLog, LogCategory},
+    identification::MagicItemNames,
+    map::{try_next_level, try_previous_level, Map, TileType, MAP_HEIGHT, MAP_WIDTH},
+    map_builders, raws,
+    rng::{RngStream, RunSeed},
+    settings::Settings,
+    systems::{
+        damage,
+        hunger::HungerSystem,
+        initiative::InitiativeSystem,
+        inventory::{
+            auto_pickup, get_item, player_burden, split_one, Burden, ItemCollectionSystem,
+            ItemDropSystem, ItemRemoveSystem, ItemUseSystem,
+        },
+        map_indexing::MapIndexingSystem,
+        melee_combat::MeleeCombatSystem,
+        monster_ai::MonsterAI,
+        particle_system::{cull_dead_particles, ParticleBuilder, ParticleSpawnSystem},
+        player::{player_loses_turn, skip_turn, stagger_if_confused, try_move_player},
+        ranged_combat::RangedCombatSystem,
+        saveload::ActiveSlot,
+        status::StatusSystem,
+        travel::Travel,
+        trigger::TriggerSystem,
+        visibility::FoVSystem,
+    },
+    RunState,
+};
+use bracket_lib::prelude::Point;
+use specs::{
+    prelude::*,
+    saveload::{SimpleMarker, SimpleMarkerAllocator},
+};
+
+/// Everything the player can do that takes a turn.
+/// Keyboard input and scripted runs both go through these.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PlayerCommand {
+    Move {
+        dx: i32,
+        dy: i32,
+    },
+    Wait,
+    PickUp,
+    Descend,
+    Ascend,
+    UseItem {
+        item: Entity,
+        target: Option<Point>,
+    },
+    DropItem {
+        item: Entity,
+    },
+    /// take off something that's equipped
+    RemoveItem {
+        item: Entity,
+    },
+    /// fire ammunition from the equipped launcher, or throw a throwable item
+    Shoot {
+        item: Entity,
+        target: Point,
+    },
+}
+
+/// How many full turns the run has lasted
+#[derive(Default)]
+pub struct TurnCounter {
+    pub turn: i32,
+}
+
+/// tells Specs to generate storage systems for registered components
+pub fn register_components(ecs: &mut World) {
+    ecs.register::<Position>();
+    ecs.register::<Renderable>();
+    ecs.register::<Player>();
+    ecs.register::<Monster>();
+    ecs.register::<Viewshed>();
+    ecs.register::<Name>();
+    ecs.register::<BlocksTile>();
+    ecs.register::<CombatStats>();
+    ecs.register::<WantsToMelee>();
+    ecs.register::<Item>();
+    ecs.register::<ProvidesHealing>();
+    ecs.register::<WantsToPickupItem>();
+    ecs.register::<InBackpack>();
+    ecs.register::<WantsToUseItem>();
+    ecs.register::<WantsToDropItem>();
+    ecs.register::<WantsToRemoveItem>();
+    ecs.register::<Enchantment>();
+    ecs.register::<Cursed>();
+    ecs.register::<UnknownEnchantment>();
+    ecs.register::<Identifies>();
+    ecs.register::<Weight>();
+    ecs.register::<Stackable>();
+    ecs.register::<Consumable>();
+    ecs.register::<Ranged>();
+    ecs.register::<InflictsDamage>();
+    ecs.register::<AreaOfEffect>();
+    ecs.register::<Confusion>();
+    ecs.register::<StatusEffects>();
+    ecs.register::<Initiative>();
+    ecs.register::<MyTurn>();
+    ecs.register::<InflictsStatus>();
+    ecs.register::<SimpleMarker<SerializeMe>>();
+    ecs.register::<SerializationHelper>();
+    ecs.register::<Equippable>();
+    ecs.register::<Equipped>();
+    ecs.register::<MeleePowerBonus>();
+    ecs.register::<DefenseBonus>();
+    ecs.register::<ParticleLifetime>();
+    ecs.register::<HungerState>();
+    ecs.register::<HungerClock>();
+    ecs.register::<ProvidesFood>();
+    ecs.register::<MagicMapper>();
+    ecs.register::<Teleports>();
+    ecs.register::<Hidden>();
+    ecs.register::<EntryTrigger>();
+    ecs.register::<EntityMoved>();
+    ecs.register::<SingleActivation>();
+    ecs.register::<Experience>();
+    ecs.register::<ProvidesXp>();
+    ecs.register::<Launcher>();
+    ecs.register::<Ammunition>();
+    ecs.register::<Thrown>();
+    ecs.register::<WantsToShoot>();
+    ecs.register::<AiState>();
+    ecs.register::<OtherLevelPosition>();
+}
+
+/// wipes the world and starts a fresh run from the given seed.
+/// Returns the map generation snapshots for the first level.
+pub fn new_game(ecs: &mut World, seed: RunSeed) -> Vec<Map> {
+    ecs.delete_all();
+
+    ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+    ecs.insert(Map::new(1, MAP_WIDTH, MAP_HEIGHT));
+    ecs.insert(Point::new(0, 0));
+    ecs.insert(seed);
+    ecs.insert(seed.stream(1, RngStream::Combat));
+    let player = create_player(ecs, 0, 0);
+    ecs.insert(player);
+    ecs.insert(RunState::MapGeneration);
+    let mut log = GameLog::new();
+    log.add(
+        LogCategory::General,
+        "Welcome to Stinky Roguelike!".to_string(),
+    );
+    ecs.insert(log);
+    ecs.insert(ParticleBuilder::new());
+    ecs.insert(EffectQueue::new());
+    ecs.insert(TurnCounter::default());
+    ecs.insert(ActiveSlot::default());
+    ecs.insert(MasterDungeonMap::default());
+    ecs.insert(Travel::default());
+    ecs.insert(MagicItemNames::new(
+        &raws::raws().unidentified_items(),
+        &mut seed.stream(0, RngStream::ItemNames),
+    ));
+
+    generate_world_map(ecs, 1)
+}
+
+/// runs every system once, in turn order
+pub fn run_systems(ecs: &mut World) {
+    let mut initiative = InitiativeSystem {};
+    initiative.run_now(ecs);
+    let mut fov = FoVSystem {};
+    fov.run_now(ecs);
+    let mut mob = MonsterAI {};
+    mob.run_now(ecs);
+    let mut mapindex = MapIndexingSystem {};
+    mapindex.run_now(ecs);
+    let mut triggers = TriggerSystem {};
+    triggers.run_now(ecs);
+    let mut melee = MeleeCombatSystem {};
+    melee.run_now(ecs);
+    let mut ranged = RangedCombatSystem {};
+    ranged.run_now(ecs);
+    let mut item_collection = ItemCollectionSystem {};
+    item_collection.run_now(ecs);
+    let mut items = ItemUseSystem {};
+    items.run_now(ecs);
+    let mut drop_items = ItemDropSystem {};
+    drop_items.run_now(ecs);
+    let mut remove_items = ItemRemoveSystem {};
+    remove_items.run_now(ecs);
+    let mut hunger = HungerSystem {};
+    hunger.run_now(ecs);
+    let mut status = StatusSystem {};
+    status.run_now(ecs);
+    run_effects_queue(ecs);
+    let mut particles = ParticleSpawnSystem {};
+    particles.run_now(ecs);
+    if *ecs.fetch::<RunState>() == RunState::PlayerTurn {
+        ecs.write_resource::<TurnCounter>().turn += 1;
+    }
+    let turn = ecs.fetch::<TurnCounter>().turn;
+    ecs.write_resource::<GameLog>().turn = turn;
+    ecs.maintain();
+}
+
+/// turns a command into the intent components the systems act on.
+/// Returns the state the game should move to.
+pub fn apply_command(ecs: &mut World, command: PlayerCommand) -> RunState {
+    if player_loses_turn(ecs) {
+        return RunState::PlayerTurn;
+    }
+    match command {
+        PlayerCommand::Move { dx, dy } => {
+            if player_burden(ecs).2 == Burden::Overloaded {
+                ecs.write_resource::<GameLog>().add(
+                    LogCategory::Item,
+                    "You're carrying too much to move!".to_string(),
+                );
+                return RunState::AwaitingInput;
+            }
+            let (dx, dy) = stagger_if_confused(ecs, dx, dy);
+            let start = *ecs.fetch::<Point>();
+            try_move_player(dx, dy, ecs);
+            if ecs.fetch::<Settings>().auto_pickup && *ecs.fetch::<Point>() != start {
+                auto_pickup(ecs);
+            }
+        }
+        PlayerCommand::Wait => return skip_turn(ecs),
+        PlayerCommand::PickUp => get_item(ecs),
+        PlayerCommand::Descend => {
+            if try_next_level(ecs) {
+                return RunState::NextLevel;
+            }
+        }
+        PlayerCommand::Ascend => {
+            if try_previous_level(ecs) {
+                return RunState::PreviousLevel;
+            }
+        }
+        PlayerCommand::UseItem { item, target } => {
+            let item = split_one(ecs, item);
+            let mut intent = ecs.write_storage::<WantsToUseItem>();
+            intent
+                .insert(*ecs.fetch::<Entity>(), WantsToUseItem { item, target })
+                .expect("Unable to insert intent");
+        }
+        PlayerCommand::DropItem { item } => {
+            let item = split_one(ecs, item);
+            let mut intent = ecs.write_storage::<WantsToDropItem>();
+            intent
+                .insert(*ecs.fetch::<Entity>(), WantsToDropItem { item })
+                .expect("Unable to insert intent");
+        }
+        PlayerCommand::RemoveItem { item } => {
+            let mut intent = ecs.write_storage::<WantsToRemoveItem>();
+            intent
+                .insert(*ecs.fetch::<Entity>(), WantsToRemoveItem { item })
+                .expect("Unable to insert intent");
+        }
+        PlayerCommand::Shoot { item, target } => {
+            let item = split_one(ecs, item);
+            let mut intent = ecs.write_storage::<WantsToShoot>();
+            intent
+                .insert(*ecs.fetch::<Entity>(), WantsToShoot { item, target })
+                .expect("Unable to insert intent");
+        }
+    }
+    RunState::PlayerTurn
+}
+
+/// leaves the current level for the one below, building it if it's never been visited.
+/// Returns the map generation snapshots, empty when the level was already built.
+pub fn goto_next_level(ecs: &mut World) -> Vec<Map> {
+    let current_depth = ecs.fetch::<Map>().depth;
+    let history = change_level(ecs, current_depth + 1);
+
+    let player_entity = ecs.fetch::<Entity>();
+    //Notify player, give small health bump
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    gamelog.add(
+        LogCategory::General,
+        "You descend further into the mountain. Take a moment to rest.".to_string(),
+    );
+    let mut player_health_store = ecs.write_storage::<CombatStats>();
+    let player_health = player_health_store.get_mut(*player_entity);
+    if let Some(player_health) = player_health {
+        player_health.hp = i32::max(player_health.hp, player_health.max_hp / 2)
+    }
+
+    history
+}
+
+/// climbs back up to the level above, which is always one the player has already been to
+pub fn goto_previous_level(ecs: &mut World) -> Vec<Map> {
+    let current_depth = ecs.fetch::<Map>().depth;
+    let history = change_level(ecs, current_depth - 1);
+
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    gamelog.add(
+        LogCategory::General,
+        "You climb back towards the surface.".to_string(),
+    );
+
+    history
+}
+
+/// Stores away the current level and everything on it, then moves the player to new_depth.
+/// A stored level is restored with the player on the stairs they came through.
+fn change_level(ecs: &mut World, new_depth: i32) -> Vec<Map> {
+    let old_depth = ecs.fetch::<Map>().depth;
+    freeze_level_entities(ecs);
+    {
+        let map = ecs.fetch::<Map>();
+        ecs.write_resource::<MasterDungeonMap>().store_map(&map);
+    }
+
+    let stored = ecs.fetch::<MasterDungeonMap>().get_map(new_depth);
+    match stored {
+        None => generate_world_map(ecs, new_depth),
+        Some(map) => {
+            // going down you arrive on the way up, and the other way around
+            let arrival = if new_depth > old_depth {
+                TileType::UpStairs
+            } else {
+                TileType::DownStairs
+            };
+            let start = map
+                .tiles
+                .iter()
+                .position(|tile| *tile == arrival)
+                .map(|idx| Point::new(idx as i32 % map.width, idx as i32 / map.width));
+            *ecs.write_resource::<Map>() = map;
+            thaw_level_entities(ecs);
+            ecs.insert(FlowField::default());
+            if let Some(start) = start {
+                place_player(ecs, start);
+            }
+            Vec::new()
+        }
+    }
+}
+
+/// builds a level for the given depth, stores it as the active map and moves the player to its start.
+/// Returns the map generation snapshots.
+pub fn generate_world_map(ecs: &mut World, new_depth: i32) -> Vec<Map> {
+    let seed = *ecs.fetch::<RunSeed>();
+    let mut mapgen_rng = seed.stream(new_depth, RngStream::MapGen);
+    let mut builder = map_builders::random_builder(new_depth, &mut mapgen_rng);
+    builder.build_map(&mut mapgen_rng);
+    let player_start = builder.get_starting_position();
+    {
+        let mut worldmap_resource = ecs.write_resource::<Map>();
+        *worldmap_resource = builder.get_map();
+        // every level below the first is reached from above, so the way back starts where the player does
+        if new_depth > 1 {
+            let start_idx = worldmap_resource.xy_idx(player_start.x, player_start.y);
+            worldmap_resource.tiles[start_idx] = TileType::UpStairs;
+        }
+    }
+
+    // spawning and combat each get a fresh stream per level, so the same seed
+    // always gives the same level no matter what happened on the one before
+    ecs.insert(seed.stream(new_depth, RngStream::Spawn));
+    builder.spawn_entities(ecs);
+    ecs.insert(seed.stream(new_depth, RngStream::Combat));
+    ecs.insert(FlowField::default());
+
+    place_player(ecs, Point::new(player_start.x, player_start.y));
+
+    builder.get_snapshot_history()
+}
+
+/// moves the player straight to a spot on the current map, e.g. after changing level
+fn place_player(ecs: &mut World, start: Point) {
+    let mut player_pos = ecs.write_resource::<Point>();
+    *player_pos = start;
+    let mut position_components = ecs.write_storage::<Position>();
+    let player_entity = ecs.fetch::<Entity>();
+    let player_pos_comp = position_components.get_mut(*player_entity);
+    if let Some(player_pos_comp) = player_pos_comp {
+        player_pos_comp.x = start.x;
+        player_pos_comp.y = start.y;
+    }
+
+    // set viewshed to dirty
+    let mut viewshed_components = ecs.write_storage::<Viewshed>();
+    let vs = viewshed_components.get_mut(*player_entity);
+    if let Some(vs) = vs {
+        vs.dirty = true;
+    }
+}
+
+/// The game without a window. Feed it commands with `step` and inspect `ecs` afterwards;
+/// useful for tests and for letting bots play thousands of games.
+pub struct GameWorld {
+    pub ecs: World,
+}
+
+impl GameWorld {
+    /// builds a ready-to-play world, first level generated and first turn run
+    pub fn new(seed: RunSeed) -> GameWorld {
+        let mut world = GameWorld { ecs: World::new() };
+        register_components(&mut world.ecs);
+        // headless runs play the same way whatever the player has set up
+        world.ecs.insert(Settings::default());
+        new_game(&mut world.ecs, seed);
+        world.advance(RunState::PreRun);
+        world
+    }
+
+    /// plays one player command and everyone else's turns until the player's next one, the same way the windowed game does.
+    /// Returns AwaitingInput once the world is ready for the next command.
+    pub fn step(&mut self, command: PlayerCommand) -> RunState {
+        let runstate = apply_command(&mut self.ecs, command);
+        self.advance(runstate)
+    }
+
+    pub fn depth(&self) -> i32 {
+        self.ecs.fetch::<Map>().depth
+    }
+
+    pub fn player_is_dead(&self) -> bool {
+        let player_entity = self.ecs.fetch::<Entity>();
+        let combat_stats = self.ecs.read_storage::<CombatStats>();
+        match combat_stats.get(*player_entity) {
+            None => true,
+            Some(stats) => stats.hp < 1,
+        }
+    }
+
+    /// runs turn states until the game is waiting on the player again
+    fn advance(&mut self, mut runstate: RunState) -> RunState {
+        loop {
+            *self.ecs.write_resource::<RunState>() = runstate;
+            match runstate {
+                RunState::PreRun => {
+                    run_systems(&mut self.ecs);
+                    runstate = RunState::AwaitingInput;
+                }
+                RunState::Ticking => {
+                    // the initiative system stops the clock once it's the player's turn
+                    run_systems(&mut self.ecs);
+                    runstate = *self.ecs.fetch::<RunState>();
+                }
+                RunState::PlayerTurn => {
+                    run_systems(&mut self.ecs);
+                    let current = *self.ecs.fetch::<RunState>();
+                    if let RunState::MagicMapReveal { .. } = current {
+                        // no one is watching, so reveal the whole map in one go
+                        let mut map = self.ecs.fetch_mut::<Map>();
+                        for tile in map.revealed_tiles.iter_mut() {
+                            *tile = true;
+                        }
+                    }
+                    runstate = RunState::Ticking;
+                }
+                RunState::NextLevel => {
+                    goto_next_level(&mut self.ecs);
+                    runstate = RunState::PreRun;
+                }
+                RunState::PreviousLevel => {
+                    goto_previous_level(&mut self.ecs);
+                    runstate = RunState::PreRun;
+                }
+                _ => return runstate,
+            }
+            damage::delete_dead(&mut self.ecs);
+            cull_dead_particles(&mut self.ecs, f32::MAX);
+        }
+    }
+}
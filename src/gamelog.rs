@@ -1,3 +1,163 @@
+// module for the message log: what happened, on which turn, and what kind of thing it was
+use bracket_lib::prelude::*;
+use std::collections::VecDeque;
+
+/// the oldest messages are forgotten once the log holds this many
+pub const MAX_LOG_ENTRIES: usize = 500;
+
+/// What a message is about, which decides its colour and lets the history be filtered
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LogCategory {
+    General,
+    Combat,
+    Item,
+    Hunger,
+    /// things the player has found out about the dungeon
+    Discovery,
+}
+
+impl LogCategory {
+    pub const ALL: [LogCategory; 5] = [
+        LogCategory::General,
+        LogCategory::Combat,
+        LogCategory::Item,
+        LogCategory::Hunger,
+        LogCategory::Discovery,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LogCategory::General => "General",
+            LogCategory::Combat => "Combat",
+            LogCategory::Item => "Items",
+            LogCategory::Hunger => "Hunger",
+            LogCategory::Discovery => "Discovery",
+        }
+    }
+
+    pub fn color(self) -> RGB {
+        match self {
+            LogCategory::General => RGB::named(WHITE),
+            LogCategory::Combat => RGB::named(SALMON),
+            LogCategory::Item => RGB::named(CYAN),
+            LogCategory::Hunger => RGB::named(ORANGE),
+            LogCategory::Discovery => RGB::named(LIGHT_GREEN),
+        }
+    }
+}
+
+pub struct LogEntry {
+    /// the turn the message was last said on
+    pub turn: i32,
+    pub category: LogCategory,
+    pub text: String,
+    pub color: RGB,
+    /// how many times in a row it's been said
+    pub count: u32,
+}
+
+impl LogEntry {
+    /// the message as shown, with any repeats counted on the end
+    pub fn display(&self) -> String {
+        if self.count > 1 {
+            format!("{} x{}", self.text, self.count)
+        } else {
+            self.text.clone()
+        }
+    }
+}
+
 pub struct GameLog {
-    pub entries: Vec<String>,
+    entries: VecDeque<LogEntry>,
+    /// new messages are stamped with this; run_systems keeps it in step with the TurnCounter
+    pub turn: i32,
+}
+
+impl GameLog {
+    pub fn new() -> GameLog {
+        GameLog {
+            entries: VecDeque::new(),
+            turn: 0,
+        }
+    }
+
+    /// adds a message in its category's colour
+    pub fn add(&mut self, category: LogCategory, text: String) {
+        self.add_colored(category, text, category.color());
+    }
+
+    /// Adds a message in a colour of its own. A message that repeats the last one
+    /// bumps its count instead of taking up another line.
+    pub fn add_colored(&mut self, category: LogCategory, text: String, color: RGB) {
+        if let Some(last) = self.entries.back_mut() {
+            if last.text == text && last.category == category {
+                last.count += 1;
+                last.turn = self.turn;
+                return;
+            }
+        }
+        if self.entries.len() >= MAX_LOG_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(LogEntry {
+            turn: self.turn,
+            category,
+            text,
+            color,
+            count: 1,
+        });
+    }
+
+    /// the messages, oldest first
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &LogEntry> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_repeat_bumps_the_count_and_turn() {
+        let mut log = GameLog::new();
+        log.add(LogCategory::Combat, "Goblin hits you".to_string());
+        log.turn = 3;
+        log.add(LogCategory::Combat, "Goblin hits you".to_string());
+        log.add(LogCategory::Combat, "Goblin hits you".to_string());
+
+        let entries: Vec<&LogEntry> = log.entries().collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].count, 3);
+        assert_eq!(entries[0].turn, 3);
+        assert_eq!(entries[0].display(), "Goblin hits you x3");
+    }
+
+    #[test]
+    fn the_same_text_in_another_category_is_kept_apart() {
+        let mut log = GameLog::new();
+        log.add(LogCategory::Combat, "Ouch".to_string());
+        log.add(LogCategory::Hunger, "Ouch".to_string());
+
+        let entries: Vec<&LogEntry> = log.entries().collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].count, 1);
+        assert_eq!(entries[1].category, LogCategory::Hunger);
+    }
+
+    #[test]
+    fn a_full_log_forgets_its_oldest_message() {
+        let mut log = GameLog::new();
+        for i in 0..=MAX_LOG_ENTRIES {
+            log.add(LogCategory::General, format!("message {}", i));
+        }
+
+        let entries: Vec<&LogEntry> = log.entries().collect();
+        assert_eq!(entries.len(), MAX_LOG_ENTRIES);
+        assert_eq!(entries[0].text, "message 1");
+        assert_eq!(
+            entries[MAX_LOG_ENTRIES - 1].text,
+            format!("message {}", MAX_LOG_ENTRIES)
+        );
+    }
 }
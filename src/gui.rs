@@ -1,8 +1,16 @@
 use crate::{
-    components::{CombatStats, InBackpack, Name, Player, Position, Viewshed},
-    gamelog::GameLog,
+    camera::Camera,
+    components::{
+        CombatStats, EquipmentSlot, Equipped, Experience, InBackpack, Name, Player, Position,
+        StatusEffects, StatusKind, Viewshed,
+    },
+    describe::{capitalize, describe},
+    gamelog::{GameLog, LogCategory, LogEntry},
+    identification::display_name,
     input::menu_input,
-    Hidden, HungerClock, HungerState, Map, Point, State,
+    keymap::{Command, Keymap},
+    systems::inventory::{player_burden, Burden},
+    Hidden, HungerClock, HungerState, Item, Map, Point, Stackable, State, Thrown,
 };
 use bracket_lib::prelude::*;
 use specs::prelude::*;
@@ -18,6 +26,7 @@ pub enum ItemMenuResult {
 pub enum MainMenuSelection {
     NewGame,
     LoadGame,
+    Options,
     Quit,
 }
 
@@ -27,35 +36,145 @@ pub enum MainMenuResult {
     Selected { selected: MainMenuSelection },
 }
 
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum SlotMenuResult {
+    NoSelection { selected: usize },
+    Cancel,
+    Load { slot: usize },
+    Delete { slot: usize },
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum OptionsMenuResult {
+    NoSelection { selected: usize },
+    Keybindings,
+    Close,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum KeymapMenuResult {
+    NoSelection { selected: usize, capturing: bool },
+    Close,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MessageLogResult {
+    Open {
+        scroll: usize,
+        filter: Option<LogCategory>,
+    },
+    Close,
+}
+
+/// lines an item menu shows before it moves on to another page
+const MENU_PAGE_SIZE: usize = 20;
+
 /// Draws inventory menu to screen.
 /// TODO: Fix display, currently messed up when FOV enters player's zone
 pub fn show_inventory(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Option<Entity>) {
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let names = gs.ecs.read_storage::<Name>();
-    let backpack = gs.ecs.read_storage::<InBackpack>();
-    let entities = gs.ecs.entities();
+    let items = player_items(&gs.ecs, |_ecs, _item| true);
+    let (carried, capacity, _burden) = player_burden(&gs.ecs);
+    let title = format!("Inventory ({:.1}/{:.0} lbs)", carried, capacity);
+    item_menu(gs, ctx, &title, items)
+}
 
-    let inventory = (&backpack, &names)
+pub fn drop_item_menu(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Option<Entity>) {
+    let items = player_items(&gs.ecs, |_ecs, _item| true);
+    item_menu(gs, ctx, "Drop which item?", items)
+}
+
+/// Lists throwable items in the player's pack
+pub fn throw_item_menu(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Option<Entity>) {
+    let items = player_items(&gs.ecs, |ecs, item| {
+        ecs.read_storage::<Thrown>().get(item).is_some()
+    });
+    item_menu(gs, ctx, "Throw which item?", items)
+}
+
+/// the items in the player's pack that pass the filter
+fn player_items(ecs: &World, filter: impl Fn(&World, Entity) -> bool) -> Vec<Entity> {
+    let player_entity = ecs.fetch::<Entity>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let entities = ecs.entities();
+    (&entities, &backpack)
         .join()
-        .filter(|item| item.0.owner == *player_entity);
-    let count = inventory.count();
+        .filter(|(item, pack)| pack.owner == *player_entity && filter(ecs, *item))
+        .map(|(item, _pack)| item)
+        .collect()
+}
+
+/// Folds identical stackable items into one line each, e.g. ("Health Potion", [a stack of 3]).
+/// Stacks merge when picked up, so this only catches one split off for a use that didn't happen.
+/// Lines are sorted by name so the letters don't jump around as things are picked up.
+fn stack_items(ecs: &World, items: Vec<Entity>) -> Vec<(String, Vec<Entity>)> {
+    let stackable = ecs.read_storage::<Stackable>();
+    let mut lines: Vec<(String, Vec<Entity>)> = Vec::new();
+    for item in items {
+        let name = display_name(ecs, item);
+        let existing = if stackable.get(item).is_some() {
+            lines.iter().position(|(line_name, stack)| {
+                *line_name == name && stackable.get(stack[0]).is_some()
+            })
+        } else {
+            None
+        };
+        match existing {
+            Some(idx) => lines[idx].1.push(item),
+            None => lines.push((name, vec![item])),
+        }
+    }
+    lines.sort_by(|a, b| a.0.cmp(&b.0));
+    lines
+}
+
+/// how many items a menu line stands for, counting every item in each stack
+fn stack_quantity(ecs: &World, stack: &[Entity]) -> i32 {
+    let stackable = ecs.read_storage::<Stackable>();
+    stack
+        .iter()
+        .map(|item| stackable.get(*item).map_or(1, |stack| stack.quantity))
+        .sum()
+}
+
+/// Draws a paged, lettered menu of items. Picking a stacked line picks one item from the stack.
+fn item_menu(
+    gs: &mut State,
+    ctx: &mut BTerm,
+    title: &str,
+    items: Vec<Entity>,
+) -> (ItemMenuResult, Option<Entity>) {
+    let lines = stack_items(&gs.ecs, items);
+    let pages = usize::max(1, (lines.len() + MENU_PAGE_SIZE - 1) / MENU_PAGE_SIZE);
+
+    match ctx.key {
+        Some(VirtualKeyCode::PageDown) | Some(VirtualKeyCode::Right) => {
+            gs.menu_page = usize::min(gs.menu_page + 1, pages - 1)
+        }
+        Some(VirtualKeyCode::PageUp) | Some(VirtualKeyCode::Left) => {
+            gs.menu_page = gs.menu_page.saturating_sub(1)
+        }
+        _ => {}
+    }
+    // the pack may have shrunk since the page was picked
+    gs.menu_page = usize::min(gs.menu_page, pages - 1);
+
+    let page: Vec<&(String, Vec<Entity>)> = lines
+        .iter()
+        .skip(gs.menu_page * MENU_PAGE_SIZE)
+        .take(MENU_PAGE_SIZE)
+        .collect();
+    let count = page.len();
 
     let mut y = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
         y - 2,
-        31,
+        40,
         (count + 3) as i32,
         RGB::named(WHITE),
         RGB::named(BLACK),
     );
-    ctx.print_color(
-        18,
-        y - 2,
-        RGB::named(YELLOW),
-        RGB::named(BLACK),
-        "Inventory",
-    );
+    ctx.print_color(18, y - 2, RGB::named(YELLOW), RGB::named(BLACK), title);
     ctx.print_color(
         18,
         y + count as i32 + 1,
@@ -63,47 +182,59 @@ pub fn show_inventory(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Optio
         RGB::named(BLACK),
         "ESC to cancel",
     );
+    if pages > 1 {
+        ctx.print_color(
+            36,
+            y + count as i32 + 1,
+            RGB::named(YELLOW),
+            RGB::named(BLACK),
+            &format!("PgUp/PgDn {}/{}", gs.menu_page + 1, pages),
+        );
+    }
 
-    let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
-        .join()
-        .filter(|item| item.1.owner == *player_entity)
-    {
+    let mut choices: Vec<Entity> = Vec::new();
+    for (j, (name, stack)) in page.iter().enumerate() {
         ctx.set(17, y, RGB::named(WHITE), RGB::named(BLACK), to_cp437('('));
         ctx.set(
             18,
             y,
-            RGB::named(WHITE),
+            RGB::named(YELLOW),
             RGB::named(BLACK),
             97 + j as FontCharType,
         );
         ctx.set(19, y, RGB::named(WHITE), RGB::named(BLACK), to_cp437(')'));
 
-        ctx.print(21, y, &name.name.to_string());
-        equippable.push(entity);
+        let quantity = stack_quantity(&gs.ecs, stack);
+        if quantity > 1 {
+            ctx.print(21, y, &format!("{} (x{})", name, quantity));
+        } else {
+            ctx.print(21, y, name);
+        }
+        choices.push(stack[0]);
         y += 1;
-        j += 1;
     }
-    menu_input(ctx, count, &mut equippable)
+
+    let result = menu_input(ctx, count, &mut choices);
+    if result.0 != ItemMenuResult::NoResponse {
+        gs.menu_page = 0;
+    }
+    result
 }
 
-pub fn drop_item_menu(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Option<Entity>) {
+/// Lists every equipment slot and what the player is wearing in it.
+/// Picking a worn item takes it off.
+pub fn show_equipment(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Option<Entity>) {
     let player_entity = gs.ecs.fetch::<Entity>();
     let names = gs.ecs.read_storage::<Name>();
-    let backpack = gs.ecs.read_storage::<InBackpack>();
+    let equipped = gs.ecs.read_storage::<Equipped>();
     let entities = gs.ecs.entities();
 
-    let inventory = (&backpack, &names)
-        .join()
-        .filter(|item| item.0.owner == *player_entity);
-    let count = inventory.count();
-
+    let count = EquipmentSlot::ALL.len();
     let mut y = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
         y - 2,
-        31,
+        45,
         (count + 3) as i32,
         RGB::named(WHITE),
         RGB::named(BLACK),
@@ -113,38 +244,43 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Optio
         y - 2,
         RGB::named(YELLOW),
         RGB::named(BLACK),
-        "Drop which item?",
+        "Equipment",
     );
     ctx.print_color(
-        19,
+        18,
         y + count as i32 + 1,
-        RGB::named(WHITE),
+        RGB::named(YELLOW),
         RGB::named(BLACK),
-        "Escape to cancel",
+        "Select to unequip, ESC to cancel",
     );
 
-    let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
-        .join()
-        .filter(|item| item.1.owner == *player_entity)
-    {
-        ctx.set(17, y, RGB::named(WHITE), RGB::named(BLACK), to_cp437('('));
-        ctx.set(
-            18,
-            y,
-            RGB::named(YELLOW),
-            RGB::named(BLACK),
-            97 + j as FontCharType,
-        );
-        ctx.set(19, y, RGB::named(WHITE), RGB::named(BLACK), to_cp437(')'));
+    let mut worn: Vec<Entity> = Vec::new();
+    for slot in EquipmentSlot::ALL.iter() {
+        let item = (&entities, &equipped, &names)
+            .join()
+            .find(|(_item, worn, _name)| worn.owner == *player_entity && worn.slot == *slot);
 
-        ctx.print(21, y, &name.name.to_string());
-        equippable.push(entity);
+        ctx.print_color(21, y, RGB::named(GRAY), RGB::named(BLACK), slot.name());
+        match item {
+            Some((entity, _worn, _name)) => {
+                ctx.set(17, y, RGB::named(WHITE), RGB::named(BLACK), to_cp437('('));
+                ctx.set(
+                    18,
+                    y,
+                    RGB::named(YELLOW),
+                    RGB::named(BLACK),
+                    97 + worn.len() as FontCharType,
+                );
+                ctx.set(19, y, RGB::named(WHITE), RGB::named(BLACK), to_cp437(')'));
+                ctx.print(33, y, &display_name(&gs.ecs, entity));
+                worn.push(entity);
+            }
+            None => ctx.print_color(33, y, RGB::named(GRAY), RGB::named(BLACK), "-"),
+        }
         y += 1;
-        j += 1;
     }
-    menu_input(ctx, count, &mut equippable)
+    let count = worn.len();
+    menu_input(ctx, count, &mut worn)
 }
 
 // Draws UI element over screen
@@ -168,7 +304,7 @@ pub fn draw_ui(ecs: &World, ctx: &mut BTerm) {
         ctx.draw_bar_horizontal(
             28,
             43,
-            51,
+            30,
             stats.hp,
             stats.max_hp,
             RGB::named(RED),
@@ -189,14 +325,42 @@ pub fn draw_ui(ecs: &World, ctx: &mut BTerm) {
         }
     }
 
+    // active statuses sit just above the health bar, stopping short of the burden and hunger displays
+    let statuses = ecs.read_storage::<StatusEffects>();
+    for (_player, status) in (&players, &statuses).join() {
+        let mut x = 12;
+        for effect in status.effects.iter() {
+            let label = format!("{} {}", capitalize(effect.kind.adjective()), effect.turns);
+            if x + label.len() as i32 > 58 {
+                break;
+            }
+            ctx.print_color(x, 42, status_color(effect.kind), RGB::named(BLACK), &label);
+            x += label.len() as i32 + 1;
+        }
+    }
+
+    match player_burden(ecs).2 {
+        Burden::Unburdened => {}
+        Burden::Burdened => {
+            ctx.print_color(60, 42, RGB::named(ORANGE), RGB::named(BLACK), "Burdened")
+        }
+        Burden::Overloaded => {
+            ctx.print_color(60, 42, RGB::named(RED), RGB::named(BLACK), "Overloaded")
+        }
+    }
+
+    let experience = ecs.read_storage::<Experience>();
+    for (_player, exp) in (&players, &experience).join() {
+        let level = format!("Lv {} XP {}/{}", exp.level, exp.xp, exp.next_level_xp());
+        ctx.print_color(60, 43, RGB::named(GOLD), RGB::named(BLACK), &level);
+    }
+
     // Grabs entries from the game log struct and prints them in reverse order.
     // TODO: See if I can slow down time between turns so that the game log is less jarring
     let log = ecs.fetch::<GameLog>();
     let mut y = 44;
-    for s in log.entries.iter().rev() {
-        if y < 49 {
-            ctx.print(2, y, s);
-        }
+    for entry in log.entries().rev().take(5) {
+        ctx.print_color(2, y, entry.color, RGB::named(BLACK), entry.display());
         y += 1;
     }
 
@@ -208,24 +372,56 @@ pub fn draw_ui(ecs: &World, ctx: &mut BTerm) {
     draw_tooltips(ecs, ctx);
 }
 
+/// the colour a status shows in on the ui
+fn status_color(kind: StatusKind) -> RGB {
+    match kind {
+        StatusKind::Confused => RGB::named(MAGENTA),
+        StatusKind::Poisoned => RGB::named(GREEN),
+        StatusKind::Slowed => RGB::named(CYAN),
+        StatusKind::Hasted => RGB::named(YELLOW),
+        StatusKind::Paralysed => RGB::named(LIGHTBLUE),
+        StatusKind::Regenerating => RGB::named(PINK),
+    }
+}
+
 /// Draws tooltips over screen
 fn draw_tooltips(ecs: &World, ctx: &mut BTerm) {
     let map = ecs.fetch::<Map>();
     let names = ecs.read_storage::<Name>();
     let positions = ecs.read_storage::<Position>();
     let hidden = ecs.read_storage::<Hidden>();
+    let statuses = ecs.read_storage::<StatusEffects>();
 
-    // Checks if mouse is on-screen
+    // Checks if mouse is over the map, and which tile it's pointing at
     let mouse_pos = ctx.mouse_pos();
-    if mouse_pos.0 >= map.width || mouse_pos.1 >= map.height {
-        return;
-    }
+    let mouse_map_pos =
+        match Camera::from_player(ecs).screen_to_world(&map, mouse_pos.0, mouse_pos.1) {
+            Some(pos) => pos,
+            None => return,
+        };
     // Initializes tooltip vector. If mouse is over a named anything at that position, push to tooltip vector
     let mut tooltip: Vec<String> = Vec::new();
-    for (name, position, _hidden) in (&names, &positions, !&hidden).join() {
+    for (entity, _name, position, _hidden) in (&ecs.entities(), &names, &positions, !&hidden).join()
+    {
         let idx = map.xy_idx(position.x, position.y);
-        if position.x == mouse_pos.0 && position.y == mouse_pos.1 && map.visible_tiles[idx] {
-            tooltip.push(name.name.to_string());
+        if position.x == mouse_map_pos.x && position.y == mouse_map_pos.y && map.visible_tiles[idx]
+        {
+            // anything under a status shows it after its name, e.g. "Goblin (poisoned, slowed)"
+            match statuses.get(entity) {
+                Some(status) if !status.effects.is_empty() => {
+                    let kinds: Vec<&str> = status
+                        .effects
+                        .iter()
+                        .map(|effect| effect.kind.adjective())
+                        .collect();
+                    tooltip.push(format!(
+                        "{} ({})",
+                        display_name(ecs, entity),
+                        kinds.join(", ")
+                    ));
+                }
+                _ => tooltip.push(display_name(ecs, entity)),
+            }
         }
     }
 
@@ -295,6 +491,341 @@ fn draw_tooltips(ecs: &World, ctx: &mut BTerm) {
     }
 }
 
+/// Look mode: a cursor moved with the movement keys or the mouse, and a panel describing
+/// whatever the player can see under it. Returns the new cursor, or None to stop looking.
+pub fn examine(gs: &mut State, ctx: &mut BTerm, cursor: Point) -> Option<Point> {
+    let map = gs.ecs.fetch::<Map>();
+    let camera = Camera::from_player(&gs.ecs);
+
+    let command = ctx
+        .key
+        .and_then(|key| gs.ecs.fetch::<Keymap>().command(key));
+    if ctx.key == Some(VirtualKeyCode::Escape) || command == Some(Command::Look) {
+        return None;
+    }
+    let step = command
+        .and_then(|command| command.direction())
+        .unwrap_or((0, 0));
+    let mut cursor = Point::new(
+        i32::clamp(cursor.x + step.0, 0, map.width - 1),
+        i32::clamp(cursor.y + step.1, 0, map.height - 1),
+    );
+    if ctx.left_click {
+        let mouse_pos = ctx.mouse_pos();
+        if let Some(clicked) = camera.screen_to_world(&map, mouse_pos.0, mouse_pos.1) {
+            cursor = clicked;
+        }
+    }
+
+    ctx.print_color(
+        5,
+        0,
+        RGB::named(YELLOW),
+        RGB::named(BLACK),
+        "Look around (ESC to stop)",
+    );
+    let screen = match camera.world_to_screen(cursor.x, cursor.y) {
+        Some(screen) => screen,
+        None => return Some(cursor),
+    };
+    ctx.set_bg(screen.x, screen.y, RGB::named(CYAN));
+
+    // describe what's on the tile, creatures first
+    let idx = map.xy_idx(cursor.x, cursor.y);
+    let mut lines: Vec<(String, RGB)> = Vec::new();
+    if !map.visible_tiles[idx] {
+        lines.push(("You can't see there.".to_string(), RGB::named(GRAY)));
+    } else {
+        let positions = gs.ecs.read_storage::<Position>();
+        let hidden = gs.ecs.read_storage::<Hidden>();
+        let names = gs.ecs.read_storage::<Name>();
+        let combat_stats = gs.ecs.read_storage::<CombatStats>();
+        let mut here: Vec<Entity> = (&gs.ecs.entities(), &positions, &names, !&hidden)
+            .join()
+            .filter(|(_entity, pos, _name, _hidden)| pos.x == cursor.x && pos.y == cursor.y)
+            .map(|(entity, _pos, _name, _hidden)| entity)
+            .collect();
+        here.sort_by_key(|entity| combat_stats.get(*entity).is_none());
+        for entity in here {
+            lines.push((display_name(&gs.ecs, entity), RGB::named(YELLOW)));
+            for line in describe(&gs.ecs, entity) {
+                lines.push((format!("  {}", line), RGB::named(WHITE)));
+            }
+        }
+        if lines.is_empty() {
+            lines.push(("Nothing of interest.".to_string(), RGB::named(GRAY)));
+        }
+    }
+
+    // the panel goes on whichever side the cursor isn't
+    let width = 38;
+    let left_x = if screen.x > 40 { 1 } else { 41 };
+    let height = usize::min(lines.len(), 38) as i32;
+    ctx.draw_box(
+        left_x,
+        1,
+        width,
+        height + 1,
+        RGB::named(WHITE),
+        RGB::named(BLACK),
+    );
+    for (i, (line, fg)) in lines.iter().take(38).enumerate() {
+        let line: String = line.chars().take(width as usize - 2).collect();
+        ctx.print_color(left_x + 1, 2 + i as i32, *fg, RGB::named(BLACK), &line);
+    }
+
+    Some(cursor)
+}
+
+/// lines of history the message log screen shows at once
+const LOG_PAGE: usize = 42;
+
+/// The full message history, newest at the bottom. Up/Down and Page Up/Down scroll back
+/// through it and Left/Right pick which category to show.
+pub fn message_log(
+    gs: &mut State,
+    ctx: &mut BTerm,
+    scroll: usize,
+    filter: Option<LogCategory>,
+) -> MessageLogResult {
+    let log = gs.ecs.fetch::<GameLog>();
+    let entries: Vec<&LogEntry> = log
+        .entries()
+        .filter(|entry| filter.map_or(true, |category| entry.category == category))
+        .collect();
+    let max_scroll = entries.len().saturating_sub(LOG_PAGE);
+    let scroll = usize::min(scroll, max_scroll);
+
+    ctx.draw_box(0, 0, 79, 49, RGB::named(WHITE), RGB::named(BLACK));
+    ctx.print_color_centered(1, RGB::named(YELLOW), RGB::named(BLACK), "Message Log");
+    let mut x = 2;
+    for option in filter_options() {
+        let name = option.map_or("All", |category| category.name());
+        let fg = if option == filter {
+            RGB::named(MAGENTA)
+        } else {
+            RGB::named(GRAY)
+        };
+        ctx.print_color(x, 2, fg, RGB::named(BLACK), name);
+        x += name.len() as i32 + 3;
+    }
+
+    let end = entries.len() - scroll;
+    let start = end.saturating_sub(LOG_PAGE);
+    let mut y = 4;
+    for entry in entries[start..end].iter() {
+        let text: String = entry.display().chars().take(69).collect();
+        ctx.print_color(
+            2,
+            y,
+            RGB::named(GRAY),
+            RGB::named(BLACK),
+            &format!("{:>5}", entry.turn),
+        );
+        ctx.print_color(9, y, entry.color, RGB::named(BLACK), &text);
+        y += 1;
+    }
+    if entries.is_empty() {
+        ctx.print_color(9, 4, RGB::named(GRAY), RGB::named(BLACK), "Nothing yet.");
+    } else {
+        ctx.print_color(
+            2,
+            47,
+            RGB::named(GRAY),
+            RGB::named(BLACK),
+            &format!("{}-{} of {}", start + 1, end, entries.len()),
+        );
+    }
+    ctx.print_color_centered(
+        48,
+        RGB::named(GRAY),
+        RGB::named(BLACK),
+        "Up/Down/PgUp/PgDn: scroll   Left/Right: filter   Escape: close",
+    );
+
+    let key = match ctx.key {
+        None => return MessageLogResult::Open { scroll, filter },
+        Some(key) => key,
+    };
+    if key == VirtualKeyCode::Escape
+        || gs.ecs.fetch::<Keymap>().command(key) == Some(Command::MessageLog)
+    {
+        return MessageLogResult::Close;
+    }
+    // scroll counts lines back from the newest
+    let scroll = match key {
+        VirtualKeyCode::Up => usize::min(scroll + 1, max_scroll),
+        VirtualKeyCode::Down => scroll.saturating_sub(1),
+        VirtualKeyCode::PageUp => usize::min(scroll + LOG_PAGE, max_scroll),
+        VirtualKeyCode::PageDown => scroll.saturating_sub(LOG_PAGE),
+        VirtualKeyCode::Home => max_scroll,
+        VirtualKeyCode::End => 0,
+        _ => scroll,
+    };
+    let filter = match key {
+        VirtualKeyCode::Left => step_filter(filter, -1),
+        VirtualKeyCode::Right => step_filter(filter, 1),
+        _ => return MessageLogResult::Open { scroll, filter },
+    };
+    MessageLogResult::Open { scroll: 0, filter }
+}
+
+/// everything, then each category on its own
+fn filter_options() -> Vec<Option<LogCategory>> {
+    let mut options = vec![None];
+    options.extend(LogCategory::ALL.iter().map(|category| Some(*category)));
+    options
+}
+
+fn step_filter(filter: Option<LogCategory>, step: i32) -> Option<LogCategory> {
+    let options = filter_options();
+    let count = options.len() as i32;
+    let index = options
+        .iter()
+        .position(|option| *option == filter)
+        .unwrap_or(0) as i32;
+    options[(index + step).rem_euclid(count) as usize]
+}
+
+/// What a right click on a tile can offer to do there
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum ContextAction {
+    Examine,
+    Walk,
+    Attack,
+    PickUp,
+    Throw,
+}
+
+impl ContextAction {
+    fn label(self) -> &'static str {
+        match self {
+            ContextAction::Examine => "Examine",
+            ContextAction::Walk => "Walk here",
+            ContextAction::Attack => "Attack",
+            ContextAction::PickUp => "Pick up",
+            ContextAction::Throw => "Throw at",
+        }
+    }
+}
+
+/// the actions that make sense on a tile the player has seen, in the order the menu lists them
+pub fn context_actions(ecs: &World, tile: Point) -> Vec<ContextAction> {
+    let map = ecs.fetch::<Map>();
+    let idx = map.xy_idx(tile.x, tile.y);
+    if !map.revealed_tiles[idx] {
+        return Vec::new();
+    }
+    let player_pos = *ecs.fetch::<Point>();
+    let player_entity = *ecs.fetch::<Entity>();
+    let here = tile == player_pos;
+    let adjacent =
+        !here && (tile.x - player_pos.x).abs() <= 1 && (tile.y - player_pos.y).abs() <= 1;
+    let visible = map.visible_tiles[idx];
+
+    let combat_stats = ecs.read_storage::<CombatStats>();
+    let items = ecs.read_storage::<Item>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let creature = visible
+        && map.tile_content[idx]
+            .iter()
+            .any(|entity| *entity != player_entity && combat_stats.get(*entity).is_some());
+    let item = map.tile_content[idx]
+        .iter()
+        .any(|entity| items.get(*entity).is_some() && hidden.get(*entity).is_none());
+    let can_throw = (
+        &ecs.read_storage::<InBackpack>(),
+        &ecs.read_storage::<Thrown>(),
+    )
+        .join()
+        .any(|(pack, _thrown)| pack.owner == player_entity);
+
+    let mut actions = vec![ContextAction::Examine];
+    if adjacent && creature {
+        actions.push(ContextAction::Attack);
+    } else if !here {
+        actions.push(ContextAction::Walk);
+    }
+    if here && item {
+        actions.push(ContextAction::PickUp);
+    }
+    if visible && !here && can_throw {
+        actions.push(ContextAction::Throw);
+    }
+    actions
+}
+
+/// A small menu of what can be done on a right-clicked tile, drawn beside it.
+/// An action is picked with its letter or a click; Escape or any other click closes the menu.
+pub fn context_menu(
+    gs: &mut State,
+    ctx: &mut BTerm,
+    tile: Point,
+) -> (ItemMenuResult, Option<ContextAction>) {
+    let actions = context_actions(&gs.ecs, tile);
+    let screen = match Camera::from_player(&gs.ecs).world_to_screen(tile.x, tile.y) {
+        Some(screen) if !actions.is_empty() => screen,
+        _ => return (ItemMenuResult::Cancel, None),
+    };
+
+    let width = 16;
+    let count = actions.len() as i32;
+    let x = i32::min(screen.x + 1, 79 - width);
+    let y = i32::min(screen.y, 49 - (count + 1));
+    ctx.set_bg(screen.x, screen.y, RGB::named(CYAN));
+    ctx.draw_box(x, y, width, count + 1, RGB::named(WHITE), RGB::named(BLACK));
+    for (j, action) in actions.iter().enumerate() {
+        let row = y + 1 + j as i32;
+        ctx.set(
+            x + 1,
+            row,
+            RGB::named(WHITE),
+            RGB::named(BLACK),
+            to_cp437('('),
+        );
+        ctx.set(
+            x + 2,
+            row,
+            RGB::named(YELLOW),
+            RGB::named(BLACK),
+            97 + j as FontCharType,
+        );
+        ctx.set(
+            x + 3,
+            row,
+            RGB::named(WHITE),
+            RGB::named(BLACK),
+            to_cp437(')'),
+        );
+        ctx.print(x + 5, row, action.label());
+    }
+
+    if ctx.left_click {
+        let (mouse_x, mouse_y) = ctx.mouse_pos();
+        if mouse_x > x && mouse_x < x + width && mouse_y > y && mouse_y <= y + count {
+            return (
+                ItemMenuResult::Selected,
+                Some(actions[(mouse_y - y - 1) as usize]),
+            );
+        }
+        return (ItemMenuResult::Cancel, None);
+    }
+    if gs.right_click {
+        return (ItemMenuResult::Cancel, None);
+    }
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(VirtualKeyCode::Escape) => (ItemMenuResult::Cancel, None),
+        Some(key) => {
+            let selection = letter_to_option(key);
+            if selection > -1 && selection < count {
+                return (ItemMenuResult::Selected, Some(actions[selection as usize]));
+            }
+            (ItemMenuResult::NoResponse, None)
+        }
+    }
+}
+
 pub fn ranged_target(
     gs: &mut State,
     ctx: &mut BTerm,
@@ -303,6 +834,8 @@ pub fn ranged_target(
     let player_entity = gs.ecs.fetch::<Entity>();
     let player_pos = gs.ecs.fetch::<Point>();
     let viewsheds = gs.ecs.read_storage::<Viewshed>();
+    let map = gs.ecs.fetch::<Map>();
+    let camera = Camera::from_player(&gs.ecs);
 
     ctx.print_color(5, 0, RGB::named(YELLOW), RGB::named(BLACK), "Select Target");
 
@@ -314,7 +847,9 @@ pub fn ranged_target(
         for idx in visible.visible_tiles.iter() {
             let distance = DistanceAlg::Pythagoras.distance2d(*player_pos, *idx);
             if distance <= range as f32 {
-                ctx.set_bg(idx.x, idx.y, RGB::named(BLUE));
+                if let Some(screen) = camera.world_to_screen(idx.x, idx.y) {
+                    ctx.set_bg(screen.x, screen.y, RGB::named(BLUE));
+                }
                 available_tiles.push(idx);
             }
         }
@@ -323,19 +858,19 @@ pub fn ranged_target(
     }
 
     let mouse_pos = ctx.mouse_pos();
+    let mouse_map_pos = camera.screen_to_world(&map, mouse_pos.0, mouse_pos.1);
     let mut valid_target = false;
-    for idx in available_tiles.iter() {
-        if idx.x == mouse_pos.0 && idx.y == mouse_pos.1 {
-            valid_target = true
+    if let Some(target) = mouse_map_pos {
+        for idx in available_tiles.iter() {
+            if idx.x == target.x && idx.y == target.y {
+                valid_target = true
+            }
         }
     }
     if valid_target {
         ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(CYAN));
         if ctx.left_click {
-            return (
-                ItemMenuResult::Selected,
-                Some(Point::new(mouse_pos.0, mouse_pos.1)),
-            );
+            return (ItemMenuResult::Selected, mouse_map_pos);
         }
     } else {
         ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(RED));
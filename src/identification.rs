@@ -0,0 +1,129 @@
+// module for magic item identification
+use crate::components::{Cursed, Enchantment, Name, UnknownEnchantment};
+use bracket_lib::prelude::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+const SCROLL_SYLLABLES: &[&str] = &[
+    "KLA", "TU", "BAR", "ADA", "NIK", "TO", "ZEL", "MOR", "FIN", "DUR", "XAN", "OBO", "QUE", "RIM",
+    "VEL", "THAR",
+];
+const POTION_LOOKS: &[&str] = &[
+    "Bubbling",
+    "Murky",
+    "Fizzy",
+    "Cloudy",
+    "Smoky",
+    "Glowing",
+    "Oily",
+    "Thick",
+    "Swirling",
+    "Sparkling",
+    "Viscous",
+    "Milky",
+];
+const POTION_COLORS: &[&str] = &[
+    "Red", "Blue", "Green", "Amber", "Violet", "Black", "Silver", "Golden", "Pink", "Teal",
+];
+
+/// What the player knows about magic items this run. Each kind of scroll and potion goes by
+/// a made-up name, rolled at the start of the run, until one of that kind is used or identified.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct MagicItemNames {
+    /// real name -> the name it goes by until identified
+    pub obfuscated: HashMap<String, String>,
+    pub identified: HashSet<String>,
+}
+
+impl MagicItemNames {
+    /// Rolls a name for every (real name, kind) pair, where kind is "scroll" or "potion".
+    /// No two items share a made-up name.
+    pub fn new(items: &[(String, String)], rng: &mut RandomNumberGenerator) -> MagicItemNames {
+        let mut obfuscated = HashMap::new();
+        let mut used: HashSet<String> = HashSet::new();
+        for (real, kind) in items.iter() {
+            let name = loop {
+                let candidate = match kind.as_str() {
+                    "potion" => potion_name(rng),
+                    _ => scroll_name(rng),
+                };
+                if used.insert(candidate.clone()) {
+                    break candidate;
+                }
+            };
+            obfuscated.insert(real.clone(), name);
+        }
+        MagicItemNames {
+            obfuscated,
+            identified: HashSet::new(),
+        }
+    }
+
+    /// anything that was never given a made-up name is known from the start
+    pub fn is_identified(&self, real: &str) -> bool {
+        !self.obfuscated.contains_key(real) || self.identified.contains(real)
+    }
+
+    /// Learns what a kind of item is. Returns true if it wasn't known before.
+    pub fn identify(&mut self, real: &str) -> bool {
+        if self.is_identified(real) {
+            return false;
+        }
+        self.identified.insert(real.to_string());
+        true
+    }
+
+    /// the name the player knows a kind of item by
+    pub fn display(&self, real: &str) -> String {
+        match self.obfuscated.get(real) {
+            Some(made_up) if !self.identified.contains(real) => made_up.clone(),
+            _ => real.to_string(),
+        }
+    }
+}
+
+fn scroll_name(rng: &mut RandomNumberGenerator) -> String {
+    let words = rng.range(1, 3);
+    let mut name = "Scroll of".to_string();
+    for _ in 0..words {
+        name.push(' ');
+        for _ in 0..rng.range(2, 4) {
+            name += SCROLL_SYLLABLES[rng.range(0, SCROLL_SYLLABLES.len() as i32) as usize];
+        }
+    }
+    name
+}
+
+fn potion_name(rng: &mut RandomNumberGenerator) -> String {
+    format!(
+        "{} {} Potion",
+        POTION_LOOKS[rng.range(0, POTION_LOOKS.len() as i32) as usize],
+        POTION_COLORS[rng.range(0, POTION_COLORS.len() as i32) as usize]
+    )
+}
+
+/// The name the player sees for an entity: the made-up one for unidentified magic items,
+/// and any enchantment the player has found out about, e.g. "+1 Dagger".
+pub fn display_name(ecs: &World, entity: Entity) -> String {
+    let name = match ecs.read_storage::<Name>().get(entity) {
+        Some(name) => ecs.fetch::<MagicItemNames>().display(&name.name),
+        None => return "something".to_string(),
+    };
+    if ecs
+        .read_storage::<UnknownEnchantment>()
+        .get(entity)
+        .is_some()
+    {
+        return name;
+    }
+
+    let mut name = match ecs.read_storage::<Enchantment>().get(entity) {
+        Some(enchantment) if enchantment.bonus != 0 => format!("{:+} {}", enchantment.bonus, name),
+        _ => name,
+    };
+    if ecs.read_storage::<Cursed>().get(entity).is_some() {
+        name += " (cursed)";
+    }
+    name
+}
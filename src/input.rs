@@ -1,65 +1,172 @@
-use crate::gui::ItemMenuResult;
-use crate::map::try_next_level;
-use crate::systems::{
-    inventory::get_item,
-    player::{skip_turn, try_move_player},
+use crate::camera::Camera;
+use crate::game_world::{apply_command, PlayerCommand};
+use crate::gui::{context_actions, ContextAction, ItemMenuResult};
+use crate::keymap::{Command, Keymap};
+use crate::systems::ranged_combat::ready_to_fire;
+use crate::systems::travel::{
+    start_travel, stop_travel, travel_step, travel_to_stairs, walk_to, Destination, Travel,
 };
-use crate::{RunState, State};
+use crate::{Map, RunState, State};
 use bracket_lib::prelude::*;
 use specs::prelude::*;
 
-/// tracks player input. TODO: add controller support
+/// Tracks player input. What each key does comes from the keymap, so it can be rebound.
+/// TODO: add controller support
 pub fn player_input(gs: &mut State, ctx: &mut BTerm) -> RunState {
-    //player movement
-    match ctx.key {
+    if gs.ecs.fetch::<Travel>().is_travelling() {
+        return continue_travel(gs, ctx);
+    }
+
+    // the mouse acts on tiles the player has seen: a right click asks what to do there
+    if ctx.left_click || gs.right_click {
+        let mouse_pos = ctx.mouse_pos();
+        let clicked = {
+            let map = gs.ecs.fetch::<Map>();
+            Camera::from_player(&gs.ecs)
+                .screen_to_world(&map, mouse_pos.0, mouse_pos.1)
+                .filter(|tile| map.revealed_tiles[map.xy_idx(tile.x, tile.y)])
+        };
+        return match clicked {
+            None => RunState::AwaitingInput,
+            Some(tile) if gs.right_click => RunState::ContextMenu {
+                x: tile.x,
+                y: tile.y,
+            },
+            Some(tile) => click_tile(gs, tile),
+        };
+    }
+
+    let command = match ctx.key {
         None => return RunState::AwaitingInput,
-        Some(key) => match key {
-            VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => {
-                try_move_player(-1, 0, &mut gs.ecs)
-            }
+        Some(key) => match gs.ecs.fetch::<Keymap>().command(key) {
+            None => return RunState::AwaitingInput,
+            Some(command) => command,
+        },
+    };
+    let command = match command {
+        Command::Drop => return RunState::ShowDropItem,
 
-            VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::L => {
-                try_move_player(1, 0, &mut gs.ecs)
-            }
+        Command::PickUp => PlayerCommand::PickUp,
 
-            VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::K => {
-                try_move_player(0, -1, &mut gs.ecs)
-            }
+        Command::Inventory => return RunState::ShowInventory,
+
+        Command::Equipment => return RunState::ShowEquipment,
 
-            VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::J => {
-                try_move_player(0, 1, &mut gs.ecs)
+        Command::Look => {
+            let player_pos = *gs.ecs.fetch::<Point>();
+            return RunState::Examine {
+                x: player_pos.x,
+                y: player_pos.y,
+            };
+        }
+
+        Command::MessageLog => {
+            return RunState::MessageLog {
+                scroll: 0,
+                filter: None,
             }
+        }
 
-            // Diagonals
-            VirtualKeyCode::Numpad9 | VirtualKeyCode::Y => try_move_player(1, -1, &mut gs.ecs),
+        Command::Explore => {
+            start_travel(&mut gs.ecs, Destination::Unexplored);
+            return RunState::AwaitingInput;
+        }
 
-            VirtualKeyCode::Numpad7 | VirtualKeyCode::U => try_move_player(-1, -1, &mut gs.ecs),
+        Command::TravelToStairs => {
+            travel_to_stairs(&mut gs.ecs);
+            return RunState::AwaitingInput;
+        }
 
-            VirtualKeyCode::Numpad3 | VirtualKeyCode::N => try_move_player(1, 1, &mut gs.ecs),
+        // Ranged attacks
+        Command::Fire => {
+            return match ready_to_fire(&gs.ecs) {
+                Some((range, item)) => RunState::ShowFireTargeting { range, item },
+                None => RunState::AwaitingInput,
+            }
+        }
 
-            VirtualKeyCode::Numpad1 | VirtualKeyCode::B => try_move_player(-1, 1, &mut gs.ecs),
+        Command::Throw => return RunState::ShowThrowItem { target: None },
 
-            VirtualKeyCode::D => return RunState::ShowDropItem,
+        Command::Options => {
+            return RunState::Options {
+                selection: 0,
+                in_game: true,
+            }
+        }
 
-            VirtualKeyCode::G => get_item(&mut gs.ecs),
+        Command::SaveAndQuit => return RunState::SaveGame,
 
-            VirtualKeyCode::I => return RunState::ShowInventory,
+        // Skip turn
+        Command::Wait => PlayerCommand::Wait,
 
-            VirtualKeyCode::Escape => return RunState::SaveGame,
+        //Level change
+        Command::Descend => PlayerCommand::Descend,
+        Command::Ascend => PlayerCommand::Ascend,
 
-            // Skip turn
-            VirtualKeyCode::Numpad5 | VirtualKeyCode::Space => return skip_turn(&mut gs.ecs),
+        movement => match movement.direction() {
+            Some((dx, dy)) => PlayerCommand::Move { dx, dy },
+            None => return RunState::AwaitingInput,
+        },
+    };
+    apply_command(&mut gs.ecs, command)
+}
 
-            //Level change
-            VirtualKeyCode::Period => {
-                if try_next_level(&mut gs.ecs) {
-                    return RunState::NextLevel;
-                }
-            }
-            _ => return RunState::AwaitingInput,
+/// A left click does the obvious thing on the tile: picks up what the player is standing on,
+/// attacks a monster next to them, or otherwise walks them there.
+fn click_tile(gs: &mut State, tile: Point) -> RunState {
+    let actions = context_actions(&gs.ecs, tile);
+    for action in [
+        ContextAction::PickUp,
+        ContextAction::Attack,
+        ContextAction::Walk,
+    ] {
+        if actions.contains(&action) {
+            return context_action(gs, tile, action);
+        }
+    }
+    RunState::AwaitingInput
+}
+
+/// carries out an action on a tile, picked from the context menu or implied by a click
+pub fn context_action(gs: &mut State, tile: Point, action: ContextAction) -> RunState {
+    let player_pos = *gs.ecs.fetch::<Point>();
+    match action {
+        ContextAction::Examine => RunState::Examine {
+            x: tile.x,
+            y: tile.y,
         },
+        ContextAction::Walk => match walk_to(&mut gs.ecs, tile.x, tile.y) {
+            Some(step) => apply_command(&mut gs.ecs, step),
+            None => RunState::AwaitingInput,
+        },
+        ContextAction::Attack => apply_command(
+            &mut gs.ecs,
+            PlayerCommand::Move {
+                dx: tile.x - player_pos.x,
+                dy: tile.y - player_pos.y,
+            },
+        ),
+        ContextAction::PickUp => apply_command(&mut gs.ecs, PlayerCommand::PickUp),
+        ContextAction::Throw => RunState::ShowThrowItem { target: Some(tile) },
+    }
+}
+
+/// Takes the next step of an auto-explore or travel for the player.
+/// Any key or click stops it, and so does a step that doesn't use up the turn.
+fn continue_travel(gs: &mut State, ctx: &mut BTerm) -> RunState {
+    if ctx.key.is_some() || ctx.left_click {
+        stop_travel(&mut gs.ecs, None);
+        return RunState::AwaitingInput;
+    }
+    let command = match travel_step(&mut gs.ecs) {
+        Some(command) => command,
+        None => return RunState::AwaitingInput,
+    };
+    let runstate = apply_command(&mut gs.ecs, command);
+    if runstate == RunState::AwaitingInput {
+        stop_travel(&mut gs.ecs, None);
     }
-    RunState::PlayerTurn
+    runstate
 }
 
 pub fn menu_input(
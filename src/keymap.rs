@@ -0,0 +1,529 @@
+// module for turning key presses into commands, so keys can be rebound to suit any keyboard
+use crate::config::config_dir;
+use bracket_lib::prelude::{console, VirtualKeyCode};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Something the player can do from the map, whichever key it's bound to
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Command {
+    MoveNorth,
+    MoveSouth,
+    MoveEast,
+    MoveWest,
+    MoveNorthEast,
+    MoveNorthWest,
+    MoveSouthEast,
+    MoveSouthWest,
+    Wait,
+    PickUp,
+    Drop,
+    Inventory,
+    Equipment,
+    Look,
+    MessageLog,
+    Explore,
+    TravelToStairs,
+    Fire,
+    Throw,
+    Descend,
+    Ascend,
+    Options,
+    SaveAndQuit,
+}
+
+impl Command {
+    /// in the order the key bindings screen lists them
+    pub const ALL: [Command; 23] = [
+        Command::MoveNorth,
+        Command::MoveSouth,
+        Command::MoveEast,
+        Command::MoveWest,
+        Command::MoveNorthEast,
+        Command::MoveNorthWest,
+        Command::MoveSouthEast,
+        Command::MoveSouthWest,
+        Command::Wait,
+        Command::PickUp,
+        Command::Drop,
+        Command::Inventory,
+        Command::Equipment,
+        Command::Look,
+        Command::MessageLog,
+        Command::Explore,
+        Command::TravelToStairs,
+        Command::Fire,
+        Command::Throw,
+        Command::Descend,
+        Command::Ascend,
+        Command::Options,
+        Command::SaveAndQuit,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Command::MoveNorth => "Move north",
+            Command::MoveSouth => "Move south",
+            Command::MoveEast => "Move east",
+            Command::MoveWest => "Move west",
+            Command::MoveNorthEast => "Move north-east",
+            Command::MoveNorthWest => "Move north-west",
+            Command::MoveSouthEast => "Move south-east",
+            Command::MoveSouthWest => "Move south-west",
+            Command::Wait => "Wait a turn",
+            Command::PickUp => "Pick up",
+            Command::Drop => "Drop",
+            Command::Inventory => "Inventory",
+            Command::Equipment => "Equipment",
+            Command::Look => "Look around",
+            Command::MessageLog => "Message log",
+            Command::Explore => "Explore",
+            Command::TravelToStairs => "Travel to stairs down",
+            Command::Fire => "Fire",
+            Command::Throw => "Throw",
+            Command::Descend => "Go down stairs",
+            Command::Ascend => "Go up stairs",
+            Command::Options => "Options",
+            Command::SaveAndQuit => "Save and quit",
+        }
+    }
+
+    /// the step a movement command takes; None for everything else
+    pub fn direction(self) -> Option<(i32, i32)> {
+        match self {
+            Command::MoveNorth => Some((0, -1)),
+            Command::MoveSouth => Some((0, 1)),
+            Command::MoveEast => Some((1, 0)),
+            Command::MoveWest => Some((-1, 0)),
+            Command::MoveNorthEast => Some((1, -1)),
+            Command::MoveNorthWest => Some((-1, -1)),
+            Command::MoveSouthEast => Some((1, 1)),
+            Command::MoveSouthWest => Some((-1, 1)),
+            _ => None,
+        }
+    }
+
+    /// Commands that always keep a key, so the player can't lock themselves
+    /// out of the options or out of saving
+    pub fn is_essential(self) -> bool {
+        matches!(self, Command::Options | Command::SaveAndQuit)
+    }
+}
+
+/// A ready-made set of bindings to start from
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Preset {
+    /// arrows, numpad and vi-keys all at once, as the game has always played
+    Classic,
+    Numpad,
+    ViKeys,
+    Wasd,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 4] = [
+        Preset::Classic,
+        Preset::Numpad,
+        Preset::ViKeys,
+        Preset::Wasd,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Classic => "Classic",
+            Preset::Numpad => "Numpad",
+            Preset::ViKeys => "Vi-keys",
+            Preset::Wasd => "WASD",
+        }
+    }
+}
+
+/// Every key that can be bound. Keys missing from here can't be named in the keymap file.
+const BINDABLE_KEYS: &[VirtualKeyCode] = &[
+    VirtualKeyCode::A,
+    VirtualKeyCode::B,
+    VirtualKeyCode::C,
+    VirtualKeyCode::D,
+    VirtualKeyCode::E,
+    VirtualKeyCode::F,
+    VirtualKeyCode::G,
+    VirtualKeyCode::H,
+    VirtualKeyCode::I,
+    VirtualKeyCode::J,
+    VirtualKeyCode::K,
+    VirtualKeyCode::L,
+    VirtualKeyCode::M,
+    VirtualKeyCode::N,
+    VirtualKeyCode::O,
+    VirtualKeyCode::P,
+    VirtualKeyCode::Q,
+    VirtualKeyCode::R,
+    VirtualKeyCode::S,
+    VirtualKeyCode::T,
+    VirtualKeyCode::U,
+    VirtualKeyCode::V,
+    VirtualKeyCode::W,
+    VirtualKeyCode::X,
+    VirtualKeyCode::Y,
+    VirtualKeyCode::Z,
+    VirtualKeyCode::Key1,
+    VirtualKeyCode::Key2,
+    VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4,
+    VirtualKeyCode::Key5,
+    VirtualKeyCode::Key6,
+    VirtualKeyCode::Key7,
+    VirtualKeyCode::Key8,
+    VirtualKeyCode::Key9,
+    VirtualKeyCode::Key0,
+    VirtualKeyCode::Numpad1,
+    VirtualKeyCode::Numpad2,
+    VirtualKeyCode::Numpad3,
+    VirtualKeyCode::Numpad4,
+    VirtualKeyCode::Numpad5,
+    VirtualKeyCode::Numpad6,
+    VirtualKeyCode::Numpad7,
+    VirtualKeyCode::Numpad8,
+    VirtualKeyCode::Numpad9,
+    VirtualKeyCode::Numpad0,
+    VirtualKeyCode::Up,
+    VirtualKeyCode::Down,
+    VirtualKeyCode::Left,
+    VirtualKeyCode::Right,
+    VirtualKeyCode::Home,
+    VirtualKeyCode::End,
+    VirtualKeyCode::PageUp,
+    VirtualKeyCode::PageDown,
+    VirtualKeyCode::Insert,
+    VirtualKeyCode::Delete,
+    VirtualKeyCode::Space,
+    VirtualKeyCode::Return,
+    VirtualKeyCode::Tab,
+    VirtualKeyCode::Back,
+    VirtualKeyCode::Escape,
+    VirtualKeyCode::Period,
+    VirtualKeyCode::Comma,
+    VirtualKeyCode::Semicolon,
+    VirtualKeyCode::Apostrophe,
+    VirtualKeyCode::Slash,
+    VirtualKeyCode::Backslash,
+    VirtualKeyCode::LBracket,
+    VirtualKeyCode::RBracket,
+    VirtualKeyCode::Minus,
+    VirtualKeyCode::Equals,
+    VirtualKeyCode::Grave,
+    VirtualKeyCode::F1,
+    VirtualKeyCode::F2,
+    VirtualKeyCode::F3,
+    VirtualKeyCode::F4,
+    VirtualKeyCode::F5,
+    VirtualKeyCode::F6,
+    VirtualKeyCode::F7,
+    VirtualKeyCode::F8,
+    VirtualKeyCode::F9,
+    VirtualKeyCode::F10,
+    VirtualKeyCode::F11,
+    VirtualKeyCode::F12,
+];
+
+/// how a key is written in the keymap file and on screen, e.g. "K", "Numpad8", "1"
+pub fn key_name(key: VirtualKeyCode) -> String {
+    let name = format!("{:?}", key);
+    match name.strip_prefix("Key") {
+        Some(digit) => digit.to_string(),
+        None => name,
+    }
+}
+
+fn key_from_name(name: &str) -> Option<VirtualKeyCode> {
+    BINDABLE_KEYS
+        .iter()
+        .find(|key| key_name(**key) == name)
+        .copied()
+}
+
+pub fn is_bindable(key: VirtualKeyCode) -> bool {
+    BINDABLE_KEYS.contains(&key)
+}
+
+/// Which key does what. A key does at most one thing; a command can have several keys.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Keymap {
+    bindings: Vec<(VirtualKeyCode, Command)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::preset(Preset::Classic)
+    }
+}
+
+impl Keymap {
+    pub fn preset(preset: Preset) -> Keymap {
+        use VirtualKeyCode::*;
+        let mut keymap = Keymap {
+            bindings: Vec::new(),
+        };
+        let arrows = [
+            (Up, Command::MoveNorth),
+            (Down, Command::MoveSouth),
+            (Right, Command::MoveEast),
+            (Left, Command::MoveWest),
+        ];
+        let numpad = [
+            (Numpad8, Command::MoveNorth),
+            (Numpad2, Command::MoveSouth),
+            (Numpad6, Command::MoveEast),
+            (Numpad4, Command::MoveWest),
+            (Numpad9, Command::MoveNorthEast),
+            (Numpad7, Command::MoveNorthWest),
+            (Numpad3, Command::MoveSouthEast),
+            (Numpad1, Command::MoveSouthWest),
+            (Numpad5, Command::Wait),
+        ];
+        let vi_keys = [
+            (K, Command::MoveNorth),
+            (J, Command::MoveSouth),
+            (L, Command::MoveEast),
+            (H, Command::MoveWest),
+            (U, Command::MoveNorthEast),
+            (Y, Command::MoveNorthWest),
+            (N, Command::MoveSouthEast),
+            (B, Command::MoveSouthWest),
+        ];
+        let wasd = [
+            (W, Command::MoveNorth),
+            (S, Command::MoveSouth),
+            (D, Command::MoveEast),
+            (A, Command::MoveWest),
+            (E, Command::MoveNorthEast),
+            (Q, Command::MoveNorthWest),
+            (C, Command::MoveSouthEast),
+            (Z, Command::MoveSouthWest),
+        ];
+        let actions = [
+            (Space, Command::Wait),
+            (G, Command::PickUp),
+            (D, Command::Drop),
+            (I, Command::Inventory),
+            (E, Command::Equipment),
+            (X, Command::Look),
+            (P, Command::MessageLog),
+            (Z, Command::Explore),
+            (M, Command::TravelToStairs),
+            (F, Command::Fire),
+            (T, Command::Throw),
+            (Period, Command::Descend),
+            (Comma, Command::Ascend),
+            (O, Command::Options),
+            (Escape, Command::SaveAndQuit),
+        ];
+
+        keymap.bind_all(&arrows);
+        match preset {
+            Preset::Classic => {
+                keymap.bind_all(&numpad);
+                keymap.bind_all(&vi_keys);
+            }
+            Preset::Numpad => keymap.bind_all(&numpad),
+            Preset::ViKeys => keymap.bind_all(&vi_keys),
+            Preset::Wasd => {}
+        }
+        keymap.bind_all(&actions);
+        if preset == Preset::Wasd {
+            // the movement keys take over D, E and Z, so what they did moves out of the way
+            keymap.bind_all(&[
+                (R, Command::Drop),
+                (Tab, Command::Equipment),
+                (V, Command::Explore),
+            ]);
+            keymap.bind_all(&wasd);
+        }
+        keymap
+    }
+
+    fn bind_all(&mut self, bindings: &[(VirtualKeyCode, Command)]) {
+        for (key, command) in bindings.iter() {
+            self.bind(*key, *command);
+        }
+    }
+
+    pub fn command(&self, key: VirtualKeyCode) -> Option<Command> {
+        self.bindings
+            .iter()
+            .find(|(bound, _command)| *bound == key)
+            .map(|(_key, command)| *command)
+    }
+
+    pub fn keys_for(&self, command: Command) -> Vec<VirtualKeyCode> {
+        self.bindings
+            .iter()
+            .filter(|(_key, bound)| *bound == command)
+            .map(|(key, _command)| *key)
+            .collect()
+    }
+
+    /// Binds the key to the command, taking it away from whatever it did before.
+    /// Returns false, leaving things as they were, if it was an essential command's last key.
+    pub fn bind(&mut self, key: VirtualKeyCode, command: Command) -> bool {
+        if let Some(old) = self.command(key) {
+            if old != command && old.is_essential() && self.keys_for(old).len() == 1 {
+                return false;
+            }
+        }
+        self.bindings.retain(|(bound, _command)| *bound != key);
+        self.bindings.push((key, command));
+        true
+    }
+
+    /// Takes every key away from the command. Returns false, and keeps them, for an essential one.
+    pub fn clear(&mut self, command: Command) -> bool {
+        if command.is_essential() {
+            return false;
+        }
+        self.bindings.retain(|(_key, bound)| *bound != command);
+        true
+    }
+
+    /// Reads the keymap file, falling back to the classic keys if there isn't one.
+    /// Keys the file doesn't recognise are skipped with a warning.
+    pub fn load() -> Keymap {
+        let data = match read_keymap_file() {
+            Some(data) => data,
+            None => return Keymap::default(),
+        };
+        let file: BTreeMap<String, Command> = match serde_json::from_str(&data) {
+            Ok(file) => file,
+            Err(e) => {
+                console::log(format!(
+                    "Unable to parse {}: {}. Using the default keys.",
+                    keymap_path().display(),
+                    e
+                ));
+                return Keymap::default();
+            }
+        };
+
+        Keymap::from_file(file)
+    }
+
+    /// the bindings read from a keymap file, key name to command
+    fn from_file(file: BTreeMap<String, Command>) -> Keymap {
+        let mut keymap = Keymap {
+            bindings: Vec::new(),
+        };
+        for (name, command) in file.into_iter() {
+            match key_from_name(&name) {
+                Some(key) => {
+                    keymap.bind(key, command);
+                }
+                None => console::log(format!("WARNING: unknown key {} in keymap", name)),
+            }
+        }
+
+        // a file edited by hand can still leave an essential command without a key
+        let defaults = Keymap::default();
+        for command in Command::ALL.iter().filter(|command| command.is_essential()) {
+            if keymap.keys_for(*command).is_empty() {
+                for key in defaults.keys_for(*command) {
+                    keymap.bind(key, *command);
+                }
+            }
+        }
+        keymap
+    }
+
+    fn to_file(&self) -> BTreeMap<String, Command> {
+        self.bindings
+            .iter()
+            .map(|(key, command)| (key_name(*key), *command))
+            .collect()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self) -> std::io::Result<()> {
+        std::fs::create_dir_all(config_dir())?;
+        let data = serde_json::to_string_pretty(&self.to_file())?;
+        std::fs::write(keymap_path(), data)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn keymap_path() -> PathBuf {
+    config_dir().join("keymap.json")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_keymap_file() -> Option<String> {
+    std::fs::read_to_string(keymap_path()).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_keymap_file() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_bindable_key_name_reads_back() {
+        for key in BINDABLE_KEYS.iter() {
+            assert_eq!(key_from_name(&key_name(*key)), Some(*key), "{:?}", key);
+        }
+    }
+
+    #[test]
+    fn presets_survive_the_keymap_file() {
+        for preset in Preset::ALL.iter() {
+            let keymap = Keymap::preset(*preset);
+            let data = serde_json::to_string_pretty(&keymap.to_file()).unwrap();
+            let loaded = Keymap::from_file(serde_json::from_str(&data).unwrap());
+            for command in Command::ALL.iter() {
+                let mut saved = keymap.keys_for(*command);
+                let mut read = loaded.keys_for(*command);
+                saved.sort_by_key(|key| key_name(*key));
+                read.sort_by_key(|key| key_name(*key));
+                assert_eq!(saved, read, "{:?} in {:?}", command, preset);
+            }
+        }
+    }
+
+    #[test]
+    fn essential_commands_keep_their_last_key() {
+        let mut keymap = Keymap::default();
+        assert!(!keymap.clear(Command::Options));
+        assert!(!keymap.bind(VirtualKeyCode::Escape, Command::Wait));
+        assert_eq!(
+            keymap.command(VirtualKeyCode::Escape),
+            Some(Command::SaveAndQuit)
+        );
+
+        // with a second key bound, the first can go
+        assert!(keymap.bind(VirtualKeyCode::F10, Command::SaveAndQuit));
+        assert!(keymap.bind(VirtualKeyCode::Escape, Command::Wait));
+        assert_eq!(
+            keymap.keys_for(Command::SaveAndQuit),
+            vec![VirtualKeyCode::F10]
+        );
+    }
+
+    #[test]
+    fn a_file_without_essential_keys_gets_the_defaults_back() {
+        let mut file = BTreeMap::new();
+        file.insert("Space".to_string(), Command::Wait);
+        let keymap = Keymap::from_file(file);
+        assert_eq!(keymap.command(VirtualKeyCode::O), Some(Command::Options));
+        assert_eq!(
+            keymap.command(VirtualKeyCode::Escape),
+            Some(Command::SaveAndQuit)
+        );
+        assert_eq!(keymap.command(VirtualKeyCode::Space), Some(Command::Wait));
+    }
+}
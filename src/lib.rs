@@ -0,0 +1,551 @@
+pub mod camera;
+pub mod components;
+pub mod config;
+pub mod describe;
+pub mod dungeon;
+pub mod effects;
+pub mod entities;
+pub mod flow_field;
+pub mod game_world;
+pub mod gamelog;
+pub mod gui;
+pub mod identification;
+pub mod input;
+pub mod keymap;
+pub mod map;
+pub mod map_builders;
+pub mod menu;
+pub mod raws;
+pub mod rex_assets;
+pub mod rng;
+pub mod settings;
+pub mod systems;
+
+extern crate bracket_lib;
+extern crate serde;
+
+use bracket_lib::prelude::*;
+
+use specs::prelude::*;
+
+use components::*;
+use game_world::{apply_command, PlayerCommand};
+use gamelog::{GameLog, LogCategory};
+use gui::{
+    draw_ui, drop_item_menu, ranged_target, ItemMenuResult, KeymapMenuResult, MainMenuResult,
+    MainMenuSelection, MessageLogResult, OptionsMenuResult, SlotMenuResult,
+};
+use input::player_input;
+use keymap::Keymap;
+use map::Map;
+use menu::{keymap_menu, load_game_menu, main_menu, options_menu};
+use rng::RunSeed;
+use settings::Settings;
+use systems::{damage, particle_system::cull_dead_particles, saveload};
+
+/// Set to true to watch each level being carved out before play starts
+pub const SHOW_MAPGEN_VISUALIZER: bool = false;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum RunState {
+    AwaitingInput,
+    PreRun,
+    PlayerTurn,
+    /// everyone else takes their turns until it comes round to the player again
+    Ticking,
+    ShowInventory,
+    ShowDropItem,
+    ShowEquipment,
+    /// look mode, with the cursor on the world tile x, y
+    Examine {
+        x: i32,
+        y: i32,
+    },
+    ShowTargeting {
+        range: i32,
+        item: Entity,
+    },
+    /// picking something to throw, at the target if one was already chosen
+    ShowThrowItem {
+        target: Option<Point>,
+    },
+    /// the message history, scrolled back scroll lines from the newest and showing one category if filtered
+    MessageLog {
+        scroll: usize,
+        filter: Option<LogCategory>,
+    },
+    /// the right-click menu for the world tile x, y
+    ContextMenu {
+        x: i32,
+        y: i32,
+    },
+    /// the options menu, opened from the main menu or, in_game, over the map
+    Options {
+        selection: usize,
+        in_game: bool,
+    },
+    /// the key bindings screen, reached from the options menu; capturing while it waits for the key to bind
+    Keybindings {
+        selection: usize,
+        capturing: bool,
+        in_game: bool,
+    },
+    ShowFireTargeting {
+        range: i32,
+        item: Entity,
+    },
+    MainMenu {
+        menu_selection: MainMenuSelection,
+    },
+    LoadGameMenu {
+        selection: usize,
+    },
+    SaveGame,
+    NextLevel,
+    PreviousLevel,
+    MagicMapReveal {
+        row: i32,
+    },
+    MapGeneration,
+}
+pub struct State {
+    pub ecs: World,
+    mapgen_next_state: Option<RunState>,
+    mapgen_history: Vec<Map>,
+    mapgen_index: usize,
+    mapgen_timer: f32,
+    /// shown on the main menu, e.g. when a save couldn't be loaded
+    menu_message: Option<String>,
+    /// the slots listed by the load game menu, read from disk when it opens
+    save_slots: Vec<saveload::SaveSlot>,
+    /// the page an item menu is showing, back to the first whenever one closes
+    menu_page: usize,
+    /// whether the right mouse button was down last frame
+    right_button_down: bool,
+    /// set for the frame the right mouse button goes down, like BTerm's left_click
+    right_click: bool,
+}
+
+impl State {
+    /// wraps a world that has been set up by game_world::new_game
+    pub fn new(ecs: World, mapgen_history: Vec<Map>) -> State {
+        let mut state = State {
+            ecs,
+            mapgen_next_state: None,
+            mapgen_history: Vec::new(),
+            mapgen_index: 0,
+            mapgen_timer: 0.0,
+            menu_message: None,
+            save_slots: Vec::new(),
+            menu_page: 0,
+            right_button_down: false,
+            right_click: false,
+        };
+        state.show_map_generation(mapgen_history, RunState::PreRun);
+        state
+    }
+
+    /// A settings file that couldn't be written goes in the game log, or on the main menu
+    /// when there's no game going.
+    fn report_config_error(&mut self, message: String) {
+        match *self.ecs.fetch::<RunState>() {
+            RunState::Options { in_game: false, .. }
+            | RunState::Keybindings { in_game: false, .. } => self.menu_message = Some(message),
+            _ => self
+                .ecs
+                .write_resource::<GameLog>()
+                .add(LogCategory::General, message),
+        }
+    }
+
+    /// plays back the snapshots of a freshly generated level, then moves on to next_state
+    fn show_map_generation(&mut self, history: Vec<Map>, next_state: RunState) {
+        self.mapgen_history = history;
+        self.mapgen_index = 0;
+        self.mapgen_timer = 0.0;
+        self.mapgen_next_state = Some(next_state);
+    }
+}
+
+impl GameState for State {
+    fn tick(&mut self, ctx: &mut BTerm) {
+        let mut newrunstate;
+        {
+            let runstate = self.ecs.fetch::<RunState>();
+            newrunstate = *runstate;
+        }
+
+        // bracket only tracks left clicks, so right clicks are picked out of the raw button state
+        let right_down = INPUT.lock().is_mouse_button_pressed(1);
+        self.right_click = right_down && !self.right_button_down;
+        self.right_button_down = right_down;
+
+        {
+            let settings = self.ecs.fetch::<Settings>();
+            ctx.post_scanlines = settings.scanlines;
+            ctx.post_screenburn = settings.screen_burn;
+        }
+        ctx.cls();
+        cull_dead_particles(&mut self.ecs, ctx.frame_time_ms);
+        match newrunstate {
+            RunState::MainMenu { .. } => {}
+            RunState::LoadGameMenu { .. } => {}
+            RunState::Options { in_game: false, .. } => {}
+            RunState::Keybindings { in_game: false, .. } => {}
+            RunState::MessageLog { .. } => {}
+            RunState::MapGeneration => {}
+            _ => {
+                camera::render_camera(&self.ecs, ctx);
+                draw_ui(&self.ecs, ctx);
+            }
+        }
+
+        match newrunstate {
+            RunState::MapGeneration => {
+                // levels being returned to were built long ago and have nothing to play back
+                if !SHOW_MAPGEN_VISUALIZER || self.mapgen_history.is_empty() {
+                    newrunstate = self.mapgen_next_state.unwrap();
+                } else {
+                    if self.mapgen_index < self.mapgen_history.len() {
+                        camera::render_debug_map(&self.mapgen_history[self.mapgen_index], ctx);
+                    }
+
+                    self.mapgen_timer += ctx.frame_time_ms;
+                    if self.mapgen_timer > 300.0 {
+                        self.mapgen_timer = 0.0;
+                        self.mapgen_index += 1;
+                        if self.mapgen_index >= self.mapgen_history.len() {
+                            newrunstate = self.mapgen_next_state.unwrap();
+                        }
+                    }
+                }
+            }
+            RunState::AwaitingInput => newrunstate = player_input(self, ctx),
+            RunState::Ticking => {
+                // the initiative system stops the clock once it's the player's turn
+                while newrunstate == RunState::Ticking {
+                    game_world::run_systems(&mut self.ecs);
+                    damage::delete_dead(&mut self.ecs);
+                    newrunstate = *self.ecs.fetch::<RunState>();
+                }
+            }
+            RunState::PlayerTurn => {
+                game_world::run_systems(&mut self.ecs);
+                match *self.ecs.fetch::<RunState>() {
+                    RunState::MagicMapReveal { .. } => {
+                        newrunstate = RunState::MagicMapReveal { row: 0 }
+                    }
+                    _ => newrunstate = RunState::Ticking,
+                }
+            }
+            RunState::PreRun => {
+                game_world::run_systems(&mut self.ecs);
+                newrunstate = RunState::AwaitingInput;
+            }
+            RunState::ShowDropItem => {
+                let result = drop_item_menu(self, ctx);
+                match result.0 {
+                    ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        let item = result.1.unwrap();
+                        newrunstate =
+                            apply_command(&mut self.ecs, PlayerCommand::DropItem { item });
+                    }
+                }
+            }
+            RunState::Examine { x, y } => {
+                newrunstate = match gui::examine(self, ctx, Point::new(x, y)) {
+                    None => RunState::AwaitingInput,
+                    Some(cursor) => RunState::Examine {
+                        x: cursor.x,
+                        y: cursor.y,
+                    },
+                }
+            }
+            RunState::MessageLog { scroll, filter } => {
+                newrunstate = match gui::message_log(self, ctx, scroll, filter) {
+                    MessageLogResult::Open { scroll, filter } => {
+                        RunState::MessageLog { scroll, filter }
+                    }
+                    MessageLogResult::Close => RunState::AwaitingInput,
+                }
+            }
+            RunState::Options { selection, in_game } => {
+                match options_menu(self, ctx, selection, in_game) {
+                    OptionsMenuResult::NoSelection { selected } => {
+                        newrunstate = RunState::Options {
+                            selection: selected,
+                            in_game,
+                        }
+                    }
+                    OptionsMenuResult::Keybindings => {
+                        newrunstate = RunState::Keybindings {
+                            selection: 0,
+                            capturing: false,
+                            in_game,
+                        }
+                    }
+                    OptionsMenuResult::Close => {
+                        let saved = self.ecs.fetch::<Settings>().save();
+                        if let Err(e) = saved {
+                            self.report_config_error(format!("Unable to save settings: {}", e));
+                        }
+                        newrunstate = if in_game {
+                            RunState::AwaitingInput
+                        } else {
+                            RunState::MainMenu {
+                                menu_selection: MainMenuSelection::Options,
+                            }
+                        };
+                    }
+                }
+            }
+            RunState::Keybindings {
+                selection,
+                capturing,
+                in_game,
+            } => match keymap_menu(self, ctx, selection, capturing, in_game) {
+                KeymapMenuResult::NoSelection {
+                    selected,
+                    capturing,
+                } => {
+                    newrunstate = RunState::Keybindings {
+                        selection: selected,
+                        capturing,
+                        in_game,
+                    }
+                }
+                KeymapMenuResult::Close => {
+                    let saved = self.ecs.fetch::<Keymap>().save();
+                    if let Err(e) = saved {
+                        self.report_config_error(format!("Unable to save key bindings: {}", e));
+                    }
+                    newrunstate = RunState::Options {
+                        selection: menu::KEYBINDINGS_ROW,
+                        in_game,
+                    };
+                }
+            },
+            RunState::ShowEquipment => {
+                let result = gui::show_equipment(self, ctx);
+                match result.0 {
+                    ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        let item = result.1.unwrap();
+                        newrunstate =
+                            apply_command(&mut self.ecs, PlayerCommand::RemoveItem { item });
+                    }
+                }
+            }
+            RunState::ShowInventory => {
+                let result = gui::show_inventory(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item = result.1.unwrap();
+                        let range = self.ecs.read_storage::<Ranged>().get(item).map(|r| r.range);
+                        if let Some(range) = range {
+                            newrunstate = RunState::ShowTargeting { range, item }
+                        } else {
+                            newrunstate = apply_command(
+                                &mut self.ecs,
+                                PlayerCommand::UseItem { item, target: None },
+                            );
+                        }
+                    }
+                }
+            }
+            RunState::ShowThrowItem { target } => {
+                let result = gui::throw_item_menu(self, ctx);
+                match result.0 {
+                    ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        let item = result.1.unwrap();
+                        let range = self.ecs.read_storage::<Thrown>().get(item).unwrap().range;
+                        let player_pos = *self.ecs.fetch::<Point>();
+                        // a target out of reach goes back to picking one
+                        match target.filter(|target| {
+                            DistanceAlg::Pythagoras.distance2d(player_pos, *target) <= range as f32
+                        }) {
+                            Some(target) => {
+                                newrunstate = apply_command(
+                                    &mut self.ecs,
+                                    PlayerCommand::Shoot { item, target },
+                                )
+                            }
+                            None => newrunstate = RunState::ShowFireTargeting { range, item },
+                        }
+                    }
+                }
+            }
+            RunState::ContextMenu { x, y } => {
+                let tile = Point::new(x, y);
+                let result = gui::context_menu(self, ctx, tile);
+                match result.0 {
+                    ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        newrunstate = input::context_action(self, tile, result.1.unwrap())
+                    }
+                }
+            }
+            RunState::ShowFireTargeting { range, item } => {
+                let target = ranged_target(self, ctx, range);
+                match target.0 {
+                    ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        newrunstate = apply_command(
+                            &mut self.ecs,
+                            PlayerCommand::Shoot {
+                                item,
+                                target: target.1.unwrap(),
+                            },
+                        );
+                    }
+                }
+            }
+            RunState::ShowTargeting { range, item } => {
+                let target = ranged_target(self, ctx, range);
+                match target.0 {
+                    ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        newrunstate = apply_command(
+                            &mut self.ecs,
+                            PlayerCommand::UseItem {
+                                item,
+                                target: target.1,
+                            },
+                        );
+                    }
+                }
+            }
+
+            RunState::MainMenu { .. } => {
+                let result = main_menu(self, ctx);
+                match result {
+                    MainMenuResult::NoSelection { selected } => {
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: selected,
+                        }
+                    }
+                    MainMenuResult::Selected { selected } => match selected {
+                        MainMenuSelection::NewGame => {
+                            self.menu_message = None;
+                            // the seed the menu shows is the one that gets played
+                            let seed = *self.ecs.fetch::<RunSeed>();
+                            let history = game_world::new_game(&mut self.ecs, seed);
+                            self.show_map_generation(history, RunState::PreRun);
+                            newrunstate = RunState::MapGeneration;
+                        }
+                        MainMenuSelection::LoadGame => {
+                            self.save_slots = saveload::list_slots();
+                            newrunstate = RunState::LoadGameMenu { selection: 0 };
+                        }
+                        MainMenuSelection::Options => {
+                            newrunstate = RunState::Options {
+                                selection: 0,
+                                in_game: false,
+                            };
+                        }
+                        MainMenuSelection::Quit => {
+                            std::process::exit(0);
+                        }
+                    },
+                }
+            }
+            RunState::LoadGameMenu { selection } => match load_game_menu(self, ctx, selection) {
+                SlotMenuResult::NoSelection { selected } => {
+                    newrunstate = RunState::LoadGameMenu {
+                        selection: selected,
+                    }
+                }
+                SlotMenuResult::Cancel => {
+                    newrunstate = RunState::MainMenu {
+                        menu_selection: MainMenuSelection::LoadGame,
+                    }
+                }
+                SlotMenuResult::Load { slot } => {
+                    let name = self.save_slots[slot].name.clone();
+                    match saveload::load_game(&mut self.ecs, &name) {
+                        Ok(()) => {
+                            self.menu_message = None;
+                            newrunstate = RunState::AwaitingInput;
+                            // permadeath: the run lives on in memory until it's saved again
+                            if let Err(e) = saveload::delete_slot(&name) {
+                                console::log(format!("Unable to remove loaded save: {}", e));
+                            }
+                        }
+                        Err(e) => {
+                            // a save that won't load is turned away before the world is touched
+                            self.menu_message = Some(e.to_string());
+                            newrunstate = RunState::MainMenu {
+                                menu_selection: MainMenuSelection::LoadGame,
+                            };
+                        }
+                    }
+                }
+                SlotMenuResult::Delete { slot } => {
+                    if let Err(e) = saveload::delete_slot(&self.save_slots[slot].name) {
+                        self.menu_message = Some(e.to_string());
+                    }
+                    self.save_slots = saveload::list_slots();
+                    if self.save_slots.is_empty() {
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: MainMenuSelection::NewGame,
+                        };
+                    } else {
+                        newrunstate = RunState::LoadGameMenu {
+                            selection: usize::min(slot, self.save_slots.len() - 1),
+                        };
+                    }
+                }
+            },
+            RunState::SaveGame => {
+                if let Err(e) = saveload::save_game(&mut self.ecs) {
+                    self.menu_message = Some(e.to_string());
+                }
+                // the saved run keeps its seed, the menu offers the next one
+                self.ecs.insert(RunSeed::for_next_run());
+                newrunstate = RunState::MainMenu {
+                    menu_selection: MainMenuSelection::LoadGame,
+                }
+            }
+
+            RunState::NextLevel => {
+                let history = game_world::goto_next_level(&mut self.ecs);
+                self.show_map_generation(history, RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
+            RunState::PreviousLevel => {
+                let history = game_world::goto_previous_level(&mut self.ecs);
+                self.show_map_generation(history, RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
+
+            RunState::MagicMapReveal { row } => {
+                let mut map = self.ecs.fetch_mut::<Map>();
+                for x in 0..map.width {
+                    let idx = map.xy_idx(x, row);
+                    map.revealed_tiles[idx] = true;
+                }
+                if row == map.height - 1 {
+                    newrunstate = RunState::Ticking;
+                } else {
+                    newrunstate = RunState::MagicMapReveal { row: row + 1 }
+                }
+            }
+        }
+
+        {
+            let mut runwriter = self.ecs.write_resource::<RunState>();
+            *runwriter = newrunstate;
+        }
+        damage::delete_dead(&mut self.ecs);
+    }
+}
@@ -1,21 +1,20 @@
-use crate::gamelog::GameLog;
+use crate::{
+    camera::{Camera, VIEW_HEIGHT, VIEW_WIDTH},
+    gamelog::{GameLog, LogCategory},
+};
 use bracket_lib::prelude::*;
 use serde::{Deserialize, Serialize};
 use specs::prelude::*;
-use std::{
-    cmp::{max, min},
-    collections::HashSet,
-};
-use {Algorithm2D, BTerm, BaseMap, Point, RandomNumberGenerator, RGB};
+use std::collections::HashSet;
+use {Algorithm2D, BTerm, BaseMap, Point, RGB};
 
-pub const MAP_WIDTH: usize = 80;
-pub const MAP_HEIGHT: usize = 43;
-pub const MAP_COUNT: usize = MAP_HEIGHT * MAP_WIDTH;
+/// size of the upper halls, deeper levels can be bigger than the screen
+pub const MAP_WIDTH: i32 = 80;
+pub const MAP_HEIGHT: i32 = 43;
 
 #[derive(Default, Serialize, Deserialize, Clone)]
 pub struct Map {
     pub tiles: Vec<TileType>,
-    pub rooms: Vec<Rectangle>,
     pub width: i32,
     pub height: i32,
     pub revealed_tiles: Vec<bool>,
@@ -86,90 +85,20 @@ impl BaseMap for Map {
 }
 
 impl Map {
-    fn apply_room_to_map(&mut self, room: &Rectangle) {
-        for y in room.y1 + 1..=room.y2 {
-            for x in room.x1 + 1..=room.x2 {
-                let idx = self.xy_idx(x, y);
-                self.tiles[idx] = TileType::Floor;
-            }
-        }
-    }
-
-    fn apply_tunnel_h(&mut self, x1: i32, x2: i32, y: i32) {
-        for x in min(x1, x2)..=max(x1, x2) {
-            let idx = self.xy_idx(x, y);
-            if idx > 0 && idx < self.width as usize * self.height as usize {
-                self.tiles[idx as usize] = TileType::Floor;
-            }
-        }
-    }
-
-    fn apply_tunnel_v(&mut self, y1: i32, y2: i32, x: i32) {
-        for y in min(y1, y2)..=max(y1, y2) {
-            let idx = self.xy_idx(x, y);
-            if idx > 0 && idx < self.width as usize * self.height as usize {
-                self.tiles[idx as usize] = TileType::Floor;
-            }
-        }
-    }
-
-    /// RNG map layout function, currently buggy
-    /// TODO: stop map gen out-of-bounds
-    pub fn new_map_room_and_corridors(new_depth: i32) -> Map {
-        const MAX_ROOMS: i32 = 30;
-        const MIN_SIZE: i32 = 6;
-        const MAX_SIZE: i32 = 10;
-
-        let mut map = Map {
-            tiles: vec![TileType::Wall; MAP_COUNT],
-            rooms: Vec::new(),
-            width: MAP_WIDTH as i32,
-            height: MAP_HEIGHT as i32,
-            revealed_tiles: vec![false; MAP_COUNT],
-            visible_tiles: vec![false; MAP_COUNT],
-            blocked: vec![false; MAP_COUNT],
-            tile_content: vec![Vec::new(); MAP_COUNT],
+    /// Generates a solid block of wall for a map builder to carve into
+    pub fn new(new_depth: i32, width: i32, height: i32) -> Map {
+        let map_count = (width * height) as usize;
+        Map {
+            tiles: vec![TileType::Wall; map_count],
+            width,
+            height,
+            revealed_tiles: vec![false; map_count],
+            visible_tiles: vec![false; map_count],
+            blocked: vec![false; map_count],
+            tile_content: vec![Vec::new(); map_count],
             depth: new_depth,
             bloodstains: HashSet::new(),
-        };
-
-        let mut rng = RandomNumberGenerator::new();
-
-        for _ in 0..MAX_ROOMS {
-            let w = rng.range(MIN_SIZE, MAX_SIZE);
-            let h = rng.range(MIN_SIZE, MAX_SIZE);
-            let x = rng.roll_dice(1, map.width - w - 1) - 1;
-            let y = rng.roll_dice(1, map.height - h - 1) - 1;
-            let new_room = Rectangle::new(x, y, w, h);
-            let mut ok = true;
-            for other_room in map.rooms.iter() {
-                if new_room.intersect(other_room) {
-                    ok = false
-                }
-            }
-            if ok {
-                map.apply_room_to_map(&new_room);
-
-                if !map.rooms.is_empty() {
-                    let (new_x, new_y) = new_room.center();
-                    let (prev_x, prev_y) = map.rooms[map.rooms.len() - 1].center();
-                    if rng.range(0, 2) == 1 {
-                        map.apply_tunnel_h(prev_x, new_x, prev_y);
-                        map.apply_tunnel_v(prev_y, new_y, new_x);
-                    } else {
-                        map.apply_tunnel_h(prev_y, new_y, prev_x);
-                        map.apply_tunnel_v(prev_x, new_x, new_y);
-                    }
-                }
-                map.rooms.push(new_room);
-            }
         }
-
-        let stairs_position = map.rooms[map.rooms.len() - 1].center();
-        let stairs_idx = map.xy_idx(stairs_position.0, stairs_position.1);
-        map.tiles[stairs_idx] = TileType::DownStairs;
-
-        map
     }
 
     fn is_exit_valid(&self, x: i32, y: i32) -> bool {
@@ -203,6 +132,7 @@ pub enum TileType {
     Wall,
     Floor,
     DownStairs,
+    UpStairs,
 }
 
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
@@ -232,31 +162,40 @@ impl Rectangle {
     }
 }
 
-/// draw function for map
-pub fn draw_map(ecs: &World, ctx: &mut BTerm) {
-    let map = ecs.fetch::<Map>();
-
-    let mut y = 0;
-    let mut x = 0;
-    for (idx, tile) in map.tiles.iter().enumerate() {
-        if map.revealed_tiles[idx] {
+/// draws the part of the map the camera is looking at
+pub fn draw_map(map: &Map, camera: &Camera, ctx: &mut BTerm) {
+    for screen_y in 0..VIEW_HEIGHT {
+        for screen_x in 0..VIEW_WIDTH {
+            let x = camera.min_x + screen_x;
+            let y = camera.min_y + screen_y;
+            if x < 0 || x >= map.width || y < 0 || y >= map.height {
+                continue;
+            }
+            let idx = map.xy_idx(x, y);
+            if !map.revealed_tiles[idx] {
+                continue;
+            }
             let glyph;
             let mut fg;
             let mut bg = RGB::from_f32(0., 0., 0.);
 
-            match tile {
+            match map.tiles[idx] {
                 TileType::Floor => {
                     fg = RGB::from_f32(0.0, 0.5, 0.5);
                     glyph = to_cp437('.');
                 }
                 TileType::Wall => {
                     fg = RGB::from_f32(0., 1.0, 0.);
-                    glyph = wall_glyph(&*map, x, y);
+                    glyph = wall_glyph(map, x, y);
                 }
                 TileType::DownStairs => {
                     fg = RGB::from_f32(0., 1.0, 1.0);
                     glyph = to_cp437('>');
                 }
+                TileType::UpStairs => {
+                    fg = RGB::from_f32(0., 1.0, 1.0);
+                    glyph = to_cp437('<');
+                }
             }
             if map.bloodstains.contains(&idx) {
                 bg = RGB::from_f32(0.75, 0., 0.)
@@ -265,12 +204,7 @@ pub fn draw_map(ecs: &World, ctx: &mut BTerm) {
                 fg = fg.to_greyscale();
                 bg = RGB::from_f32(0., 0., 0.);
             }
-            ctx.set(x, y, fg, bg, glyph);
-        }
-        x += 1;
-        if x > 79 {
-            x = 0;
-            y += 1;
+            ctx.set(screen_x, screen_y, fg, bg, glyph);
         }
     }
 }
@@ -284,9 +218,27 @@ pub fn try_next_level(world: &mut World) -> bool {
         true
     } else {
         let mut gamelog = world.write_resource::<GameLog>();
-        gamelog
-            .entries
-            .push("There is no way down from here.".to_string());
+        gamelog.add(
+            LogCategory::General,
+            "There is no way down from here.".to_string(),
+        );
+        false
+    }
+}
+
+pub fn try_previous_level(world: &mut World) -> bool {
+    let player_pos = world.fetch::<Point>();
+    let map = world.fetch::<Map>();
+    let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+
+    if map.tiles[player_idx] == TileType::UpStairs {
+        true
+    } else {
+        let mut gamelog = world.write_resource::<GameLog>();
+        gamelog.add(
+            LogCategory::General,
+            "There is no way up from here.".to_string(),
+        );
         false
     }
 }
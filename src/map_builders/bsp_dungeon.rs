@@ -0,0 +1,198 @@
+use super::{
+    common::{apply_room_to_map, draw_corridor},
+    MapBuilder,
+};
+use crate::{
+    components::Position,
+    map::{Map, Rectangle, TileType},
+    systems::spawner::spawn_room,
+    SHOW_MAPGEN_VISUALIZER,
+};
+use bracket_lib::prelude::RandomNumberGenerator;
+use specs::prelude::*;
+
+/// Binary space partition: keeps splitting the map into smaller rectangles and
+/// drops a room into each one that fits, giving tidy, non-overlapping halls.
+pub struct BspDungeonBuilder {
+    map: Map,
+    starting_position: Position,
+    depth: i32,
+    rooms: Vec<Rectangle>,
+    history: Vec<Map>,
+    rects: Vec<Rectangle>,
+}
+
+impl MapBuilder for BspDungeonBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.build(rng);
+    }
+
+    fn spawn_entities(&mut self, world: &mut World) {
+        for room in self.rooms.iter().skip(1) {
+            spawn_room(&self.map, world, room, self.depth);
+        }
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Map> {
+        self.history.clone()
+    }
+
+    fn take_snapshot(&mut self) {
+        if SHOW_MAPGEN_VISUALIZER {
+            let mut snapshot = self.map.clone();
+            for v in snapshot.revealed_tiles.iter_mut() {
+                *v = true;
+            }
+            self.history.push(snapshot);
+        }
+    }
+}
+
+impl BspDungeonBuilder {
+    pub fn new(new_depth: i32, width: i32, height: i32) -> BspDungeonBuilder {
+        BspDungeonBuilder {
+            map: Map::new(new_depth, width, height),
+            starting_position: Position { x: 0, y: 0 },
+            depth: new_depth,
+            rooms: Vec::new(),
+            history: Vec::new(),
+            rects: Vec::new(),
+        }
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator) {
+        self.rects.clear();
+        self.rects.push(Rectangle::new(
+            2,
+            2,
+            self.map.width - 5,
+            self.map.height - 5,
+        ));
+        let first_room = self.rects[0];
+        self.add_subrects(first_room);
+
+        // up to 240 attempts at placing a room
+        let mut n_rooms = 0;
+        while n_rooms < 240 {
+            let rect = self.get_random_rect(rng);
+            let candidate = self.get_random_sub_rect(rect, rng);
+
+            if self.is_possible(candidate) {
+                apply_room_to_map(&mut self.map, &candidate);
+                self.rooms.push(candidate);
+                self.add_subrects(rect);
+                self.take_snapshot();
+            }
+
+            n_rooms += 1;
+        }
+
+        // sorting left to right keeps the corridors short
+        self.rooms.sort_by(|a, b| a.x1.cmp(&b.x1));
+
+        for i in 0..self.rooms.len() - 1 {
+            let room = self.rooms[i];
+            let next_room = self.rooms[i + 1];
+            let start_x = room.x1 + rng.roll_dice(1, i32::abs(room.x1 - room.x2));
+            let start_y = room.y1 + rng.roll_dice(1, i32::abs(room.y1 - room.y2));
+            let end_x = next_room.x1 + rng.roll_dice(1, i32::abs(next_room.x1 - next_room.x2));
+            let end_y = next_room.y1 + rng.roll_dice(1, i32::abs(next_room.y1 - next_room.y2));
+            draw_corridor(&mut self.map, start_x, start_y, end_x, end_y);
+            self.take_snapshot();
+        }
+
+        let stairs = self.rooms[self.rooms.len() - 1].center();
+        let stairs_idx = self.map.xy_idx(stairs.0, stairs.1);
+        self.map.tiles[stairs_idx] = TileType::DownStairs;
+
+        let start = self.rooms[0].center();
+        self.starting_position = Position {
+            x: start.0,
+            y: start.1,
+        };
+    }
+
+    /// splits a rectangle into quarters and queues each one as a room candidate
+    fn add_subrects(&mut self, rect: Rectangle) {
+        let width = i32::abs(rect.x1 - rect.x2);
+        let height = i32::abs(rect.y1 - rect.y2);
+        let half_width = i32::max(width / 2, 1);
+        let half_height = i32::max(height / 2, 1);
+
+        self.rects
+            .push(Rectangle::new(rect.x1, rect.y1, half_width, half_height));
+        self.rects.push(Rectangle::new(
+            rect.x1,
+            rect.y1 + half_height,
+            half_width,
+            half_height,
+        ));
+        self.rects.push(Rectangle::new(
+            rect.x1 + half_width,
+            rect.y1,
+            half_width,
+            half_height,
+        ));
+        self.rects.push(Rectangle::new(
+            rect.x1 + half_width,
+            rect.y1 + half_height,
+            half_width,
+            half_height,
+        ));
+    }
+
+    fn get_random_rect(&mut self, rng: &mut RandomNumberGenerator) -> Rectangle {
+        if self.rects.len() == 1 {
+            return self.rects[0];
+        }
+        let idx = (rng.roll_dice(1, self.rects.len() as i32) - 1) as usize;
+        self.rects[idx]
+    }
+
+    fn get_random_sub_rect(&self, rect: Rectangle, rng: &mut RandomNumberGenerator) -> Rectangle {
+        let mut result = rect;
+        let rect_width = i32::max(1, i32::abs(rect.x1 - rect.x2));
+        let rect_height = i32::max(1, i32::abs(rect.y1 - rect.y2));
+
+        let w = i32::max(3, rng.roll_dice(1, i32::min(rect_width, 10)) - 1) + 1;
+        let h = i32::max(3, rng.roll_dice(1, i32::min(rect_height, 10)) - 1) + 1;
+
+        result.x1 += rng.roll_dice(1, 6) - 1;
+        result.y1 += rng.roll_dice(1, 6) - 1;
+        result.x2 = result.x1 + w;
+        result.y2 = result.y1 + h;
+
+        result
+    }
+
+    /// a room fits if it and a two tile border around it are solid wall and inside the map
+    fn is_possible(&self, rect: Rectangle) -> bool {
+        let mut expanded = rect;
+        expanded.x1 -= 2;
+        expanded.x2 += 2;
+        expanded.y1 -= 2;
+        expanded.y2 += 2;
+
+        for y in expanded.y1..=expanded.y2 {
+            for x in expanded.x1..=expanded.x2 {
+                if x < 1 || x > self.map.width - 2 || y < 1 || y > self.map.height - 2 {
+                    return false;
+                }
+                let idx = self.map.xy_idx(x, y);
+                if self.map.tiles[idx] != TileType::Wall {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
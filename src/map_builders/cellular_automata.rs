@@ -0,0 +1,139 @@
+use super::{
+    common::{
+        generate_voronoi_spawn_regions, nearest_floor,
+        remove_unreachable_areas_returning_most_distant,
+    },
+    MapBuilder,
+};
+use crate::{
+    components::Position,
+    map::{Map, TileType},
+    systems::spawner::spawn_region,
+    SHOW_MAPGEN_VISUALIZER,
+};
+use bracket_lib::prelude::RandomNumberGenerator;
+use specs::prelude::*;
+use std::collections::BTreeMap;
+
+/// Natural caverns: random noise smoothed out over several generations of a
+/// simple "count the walls around me" rule.
+pub struct CellularAutomataBuilder {
+    map: Map,
+    starting_position: Position,
+    depth: i32,
+    history: Vec<Map>,
+    noise_areas: BTreeMap<i32, Vec<usize>>,
+}
+
+impl MapBuilder for CellularAutomataBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.build(rng);
+    }
+
+    fn spawn_entities(&mut self, world: &mut World) {
+        for area in self.noise_areas.iter() {
+            spawn_region(&self.map, world, area.1, self.depth);
+        }
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Map> {
+        self.history.clone()
+    }
+
+    fn take_snapshot(&mut self) {
+        if SHOW_MAPGEN_VISUALIZER {
+            let mut snapshot = self.map.clone();
+            for v in snapshot.revealed_tiles.iter_mut() {
+                *v = true;
+            }
+            self.history.push(snapshot);
+        }
+    }
+}
+
+impl CellularAutomataBuilder {
+    pub fn new(new_depth: i32, width: i32, height: i32) -> CellularAutomataBuilder {
+        CellularAutomataBuilder {
+            map: Map::new(new_depth, width, height),
+            starting_position: Position { x: 0, y: 0 },
+            depth: new_depth,
+            history: Vec::new(),
+            noise_areas: BTreeMap::new(),
+        }
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator) {
+        // seed the map with roughly 55% floor
+        for y in 1..self.map.height - 1 {
+            for x in 1..self.map.width - 1 {
+                let roll = rng.roll_dice(1, 100);
+                let idx = self.map.xy_idx(x, y);
+                if roll > 55 {
+                    self.map.tiles[idx] = TileType::Floor
+                } else {
+                    self.map.tiles[idx] = TileType::Wall
+                }
+            }
+        }
+        self.take_snapshot();
+
+        // a tile with more than four wall neighbours, or none at all, becomes wall
+        for _i in 0..15 {
+            let mut newtiles = self.map.tiles.clone();
+
+            for y in 1..self.map.height - 1 {
+                for x in 1..self.map.width - 1 {
+                    let idx = self.map.xy_idx(x, y);
+                    let w = self.map.width as usize;
+                    let mut neighbors = 0;
+                    for neighbor in [
+                        idx - 1,
+                        idx + 1,
+                        idx - w,
+                        idx + w,
+                        idx - w - 1,
+                        idx - w + 1,
+                        idx + w - 1,
+                        idx + w + 1,
+                    ] {
+                        if self.map.tiles[neighbor] == TileType::Wall {
+                            neighbors += 1;
+                        }
+                    }
+
+                    if neighbors > 4 || neighbors == 0 {
+                        newtiles[idx] = TileType::Wall;
+                    } else {
+                        newtiles[idx] = TileType::Floor;
+                    }
+                }
+            }
+
+            self.map.tiles = newtiles;
+            self.take_snapshot();
+        }
+
+        let (start_x, start_y) = nearest_floor(&self.map, self.map.width / 2, self.map.height / 2);
+        self.starting_position = Position {
+            x: start_x,
+            y: start_y,
+        };
+        let start_idx = self.map.xy_idx(start_x, start_y);
+
+        let exit_tile = remove_unreachable_areas_returning_most_distant(&mut self.map, start_idx);
+        self.take_snapshot();
+
+        self.map.tiles[exit_tile] = TileType::DownStairs;
+        self.take_snapshot();
+
+        self.noise_areas = generate_voronoi_spawn_regions(&self.map, rng);
+    }
+}
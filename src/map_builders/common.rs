@@ -0,0 +1,126 @@
+use crate::map::{Map, Rectangle, TileType};
+use bracket_lib::prelude::*;
+use std::{
+    cmp::{max, min},
+    collections::BTreeMap,
+};
+
+pub fn apply_room_to_map(map: &mut Map, room: &Rectangle) {
+    for y in room.y1 + 1..=room.y2 {
+        for x in room.x1 + 1..=room.x2 {
+            let idx = map.xy_idx(x, y);
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+pub fn apply_horizontal_tunnel(map: &mut Map, x1: i32, x2: i32, y: i32) {
+    for x in min(x1, x2)..=max(x1, x2) {
+        let idx = map.xy_idx(x, y);
+        if idx > 0 && idx < map.width as usize * map.height as usize {
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+pub fn apply_vertical_tunnel(map: &mut Map, y1: i32, y2: i32, x: i32) {
+    for y in min(y1, y2)..=max(y1, y2) {
+        let idx = map.xy_idx(x, y);
+        if idx > 0 && idx < map.width as usize * map.height as usize {
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+/// digs an L-shaped corridor, stepping along x first and then y
+pub fn draw_corridor(map: &mut Map, x1: i32, y1: i32, x2: i32, y2: i32) {
+    let mut x = x1;
+    let mut y = y1;
+
+    while x != x2 || y != y2 {
+        if x < x2 {
+            x += 1;
+        } else if x > x2 {
+            x -= 1;
+        } else if y < y2 {
+            y += 1;
+        } else if y > y2 {
+            y -= 1;
+        }
+
+        let idx = map.xy_idx(x, y);
+        map.tiles[idx] = TileType::Floor;
+    }
+}
+
+/// returns the floor tile closest to (x, y). Used to find a start point on maps without rooms.
+pub fn nearest_floor(map: &Map, x: i32, y: i32) -> (i32, i32) {
+    let target = Point::new(x, y);
+    let mut best = (x, y);
+    let mut best_distance = f32::MAX;
+    for (idx, tile) in map.tiles.iter().enumerate() {
+        if *tile == TileType::Floor {
+            let candidate = Point::new(idx as i32 % map.width, idx as i32 / map.width);
+            let distance = DistanceAlg::Pythagoras.distance2d(target, candidate);
+            if distance < best_distance {
+                best_distance = distance;
+                best = (candidate.x, candidate.y);
+            }
+        }
+    }
+    best
+}
+
+/// walls off every floor tile the start can't reach, then returns the index of the
+/// reachable tile furthest from the start so it can be used for the stairs.
+pub fn remove_unreachable_areas_returning_most_distant(map: &mut Map, start_idx: usize) -> usize {
+    map.populate_blocked();
+    let map_starts: Vec<usize> = vec![start_idx];
+    let dijkstra_map = DijkstraMap::new(
+        map.width as usize,
+        map.height as usize,
+        &map_starts,
+        &*map,
+        200.0,
+    );
+
+    let mut exit_tile = (start_idx, 0.0f32);
+    for (i, tile) in map.tiles.iter_mut().enumerate() {
+        if *tile == TileType::Floor {
+            let distance_to_start = dijkstra_map.map[i];
+            if distance_to_start == f32::MAX {
+                *tile = TileType::Wall;
+            } else if distance_to_start > exit_tile.1 {
+                exit_tile = (i, distance_to_start);
+            }
+        }
+    }
+    exit_tile.0
+}
+
+/// splits the open floor into cellular noise regions, which stand in for rooms when spawning
+pub fn generate_voronoi_spawn_regions(
+    map: &Map,
+    rng: &mut RandomNumberGenerator,
+) -> BTreeMap<i32, Vec<usize>> {
+    let mut noise_areas: BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+    let mut noise = FastNoise::seeded(rng.roll_dice(1, 65536) as u64);
+    noise.set_noise_type(NoiseType::Cellular);
+    noise.set_frequency(0.08);
+    noise.set_cellular_distance_function(CellularDistanceFunction::Manhattan);
+
+    for y in 1..map.height - 1 {
+        for x in 1..map.width - 1 {
+            let idx = map.xy_idx(x, y);
+            if map.tiles[idx] == TileType::Floor {
+                let cell_value = (noise.get_noise(x as f32, y as f32) * 10240.0) as i32;
+                noise_areas
+                    .entry(cell_value)
+                    .or_insert_with(Vec::new)
+                    .push(idx);
+            }
+        }
+    }
+
+    noise_areas
+}
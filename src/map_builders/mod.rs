@@ -0,0 +1,65 @@
+// mod.rs for map generation
+mod bsp_dungeon;
+mod cellular_automata;
+mod common;
+mod drunkard;
+mod simple_map;
+
+use crate::{
+    components::Position,
+    map::{Map, MAP_HEIGHT, MAP_WIDTH},
+};
+use bracket_lib::prelude::RandomNumberGenerator;
+use bsp_dungeon::BspDungeonBuilder;
+use cellular_automata::CellularAutomataBuilder;
+use drunkard::DrunkardsWalkBuilder;
+use simple_map::SimpleMapBuilder;
+use specs::prelude::*;
+
+/// Every level generation algorithm implements this.
+/// build_map carves the map, spawn_entities fills it once it's been placed in the world.
+pub trait MapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator);
+    fn spawn_entities(&mut self, world: &mut World);
+    fn get_map(&self) -> Map;
+    fn get_starting_position(&self) -> Position;
+    fn get_snapshot_history(&self) -> Vec<Map>;
+    fn take_snapshot(&mut self);
+}
+
+/// Picks a generator for the given depth.
+/// The upper halls are built rooms, the deeper you go the more the mountain turns to raw cave and mine.
+pub fn random_builder(new_depth: i32, rng: &mut RandomNumberGenerator) -> Box<dyn MapBuilder> {
+    let (width, height) = map_size(new_depth);
+    match new_depth {
+        1..=2 => match rng.roll_dice(1, 2) {
+            1 => Box::new(SimpleMapBuilder::new(new_depth, width, height)),
+            _ => Box::new(BspDungeonBuilder::new(new_depth, width, height)),
+        },
+        3..=4 => match rng.roll_dice(1, 4) {
+            1 => Box::new(SimpleMapBuilder::new(new_depth, width, height)),
+            2 => Box::new(BspDungeonBuilder::new(new_depth, width, height)),
+            3 => Box::new(CellularAutomataBuilder::new(new_depth, width, height)),
+            _ => Box::new(DrunkardsWalkBuilder::winding_passages(
+                new_depth, width, height,
+            )),
+        },
+        _ => match rng.roll_dice(1, 4) {
+            1 => Box::new(CellularAutomataBuilder::new(new_depth, width, height)),
+            2 => Box::new(DrunkardsWalkBuilder::open_area(new_depth, width, height)),
+            3 => Box::new(DrunkardsWalkBuilder::open_halls(new_depth, width, height)),
+            _ => Box::new(DrunkardsWalkBuilder::winding_passages(
+                new_depth, width, height,
+            )),
+        },
+    }
+}
+
+/// The halls fit on one screen, the mines below sprawl out past it
+fn map_size(new_depth: i32) -> (i32, i32) {
+    match new_depth {
+        1..=2 => (MAP_WIDTH, MAP_HEIGHT),
+        3..=4 => (MAP_WIDTH * 5 / 4, MAP_HEIGHT * 5 / 4),
+        _ => (MAP_WIDTH * 3 / 2, MAP_HEIGHT * 3 / 2),
+    }
+}
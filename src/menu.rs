@@ -1,5 +1,10 @@
-use crate::gui::{MainMenuResult, MainMenuSelection};
+use crate::gui::{
+    KeymapMenuResult, MainMenuResult, MainMenuSelection, OptionsMenuResult, SlotMenuResult,
+};
+use crate::keymap::{is_bindable, key_name, Command, Keymap, Preset};
 use crate::rex_assets::RexAssets;
+use crate::rng::RunSeed;
+use crate::settings::{LogVerbosity, Settings, PARTICLE_DURATIONS};
 use crate::{RunState, State};
 use bracket_lib::prelude::*;
 use {BTerm, VirtualKeyCode, RGB};
@@ -8,13 +13,14 @@ pub fn main_menu(gs: &mut State, ctx: &mut BTerm) -> MainMenuResult {
     let save_exists = crate::systems::saveload::does_save_exist();
     let runstate = gs.ecs.fetch::<RunState>();
     let assets = gs.ecs.fetch::<RexAssets>();
+    let seed = gs.ecs.fetch::<RunSeed>();
     ctx.render_xp_sprite(&assets.menu, 0, 0);
     let mut y = 24;
     if let RunState::MainMenu {
         menu_selection: selection,
     } = *runstate
     {
-        ctx.draw_box_double(24, 18, 31, 10, RGB::named(WHEAT), RGB::named(BLACK));
+        ctx.draw_box_double(24, 18, 31, 11, RGB::named(WHEAT), RGB::named(BLACK));
         ctx.print_color_centered(20, RGB::named(YELLOW), RGB::named(BLACK), "Rusty Roguelike");
         ctx.print_color_centered(21, RGB::named(CYAN), RGB::named(BLACK), "by ShiaLeBeowulf");
         ctx.print_color_centered(
@@ -33,27 +39,34 @@ pub fn main_menu(gs: &mut State, ctx: &mut BTerm) -> MainMenuResult {
 
         if save_exists {
             if selection == MainMenuSelection::LoadGame {
-                ctx.print_color_centered(
-                    25,
-                    RGB::named(MAGENTA),
-                    RGB::named(BLACK),
-                    "Load Existing Save",
-                );
+                ctx.print_color_centered(25, RGB::named(MAGENTA), RGB::named(BLACK), "Load Game");
             } else {
-                ctx.print_color_centered(
-                    25,
-                    RGB::named(WHITE),
-                    RGB::named(BLACK),
-                    "Load Existing Save",
-                );
+                ctx.print_color_centered(25, RGB::named(WHITE), RGB::named(BLACK), "Load Game");
             }
             y += 1;
         }
 
+        if selection == MainMenuSelection::Options {
+            ctx.print_color_centered(26, RGB::named(MAGENTA), RGB::named(BLACK), "Options");
+        } else {
+            ctx.print_color_centered(26, RGB::named(WHITE), RGB::named(BLACK), "Options");
+        }
+
         if selection == MainMenuSelection::Quit {
-            ctx.print_color_centered(26, RGB::named(MAGENTA), RGB::named(BLACK), "Quit Game");
+            ctx.print_color_centered(27, RGB::named(MAGENTA), RGB::named(BLACK), "Quit Game");
         } else {
-            ctx.print_color_centered(26, RGB::named(WHITE), RGB::named(BLACK), "Quit Game");
+            ctx.print_color_centered(27, RGB::named(WHITE), RGB::named(BLACK), "Quit Game");
+        }
+
+        ctx.print_color_centered(
+            28,
+            RGB::named(GRAY),
+            RGB::named(BLACK),
+            &format!("Seed: {}", seed.seed),
+        );
+
+        if let Some(message) = &gs.menu_message {
+            ctx.print_color_centered(30, RGB::named(RED), RGB::named(BLACK), message);
         }
 
         match ctx.key {
@@ -73,7 +86,8 @@ pub fn main_menu(gs: &mut State, ctx: &mut BTerm) -> MainMenuResult {
                     match selection {
                         MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
                         MainMenuSelection::LoadGame => newselection = MainMenuSelection::NewGame,
-                        MainMenuSelection::Quit => newselection = MainMenuSelection::LoadGame,
+                        MainMenuSelection::Options => newselection = MainMenuSelection::LoadGame,
+                        MainMenuSelection::Quit => newselection = MainMenuSelection::Options,
                     }
 
                     if newselection == MainMenuSelection::LoadGame && !save_exists {
@@ -87,11 +101,12 @@ pub fn main_menu(gs: &mut State, ctx: &mut BTerm) -> MainMenuResult {
                     let mut newselection;
                     match selection {
                         MainMenuSelection::NewGame => newselection = MainMenuSelection::LoadGame,
-                        MainMenuSelection::LoadGame => newselection = MainMenuSelection::Quit,
+                        MainMenuSelection::LoadGame => newselection = MainMenuSelection::Options,
+                        MainMenuSelection::Options => newselection = MainMenuSelection::Quit,
                         MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame,
                     }
                     if newselection == MainMenuSelection::LoadGame && !save_exists {
-                        newselection = MainMenuSelection::Quit;
+                        newselection = MainMenuSelection::Options;
                     }
                     return MainMenuResult::NoSelection {
                         selected: newselection,
@@ -114,3 +129,378 @@ pub fn main_menu(gs: &mut State, ctx: &mut BTerm) -> MainMenuResult {
         selected: MainMenuSelection::NewGame,
     }
 }
+
+/// how many slots fit in the load menu at once; the list scrolls past this
+const VISIBLE_SLOTS: usize = 10;
+
+/// Lists the save slots read when the menu opened. Enter loads, Delete removes, Escape goes back.
+pub fn load_game_menu(gs: &mut State, ctx: &mut BTerm, selection: usize) -> SlotMenuResult {
+    let assets = gs.ecs.fetch::<RexAssets>();
+    ctx.render_xp_sprite(&assets.menu, 0, 0);
+    if gs.save_slots.is_empty() {
+        return SlotMenuResult::Cancel;
+    }
+    let selection = usize::min(selection, gs.save_slots.len() - 1);
+
+    ctx.draw_box_double(6, 9, 67, 30, RGB::named(WHEAT), RGB::named(BLACK));
+    ctx.print_color_centered(11, RGB::named(YELLOW), RGB::named(BLACK), "Load Game");
+    ctx.print_color_centered(
+        12,
+        RGB::named(GRAY),
+        RGB::named(BLACK),
+        "Enter: load   Delete: remove   Escape: back",
+    );
+
+    let first = selection.saturating_sub(VISIBLE_SLOTS - 1);
+    let mut y = 14;
+    for (i, slot) in gs
+        .save_slots
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(VISIBLE_SLOTS)
+    {
+        let fg = if i == selection {
+            RGB::named(MAGENTA)
+        } else {
+            RGB::named(WHITE)
+        };
+        match &slot.meta {
+            Some(meta) => {
+                ctx.print_color(
+                    8,
+                    y,
+                    fg,
+                    RGB::named(BLACK),
+                    &format!(
+                        "{}: {}, depth {}, turn {}",
+                        slot.name, meta.character, meta.depth, meta.turn
+                    ),
+                );
+                ctx.print_color(
+                    10,
+                    y + 1,
+                    RGB::named(GRAY),
+                    RGB::named(BLACK),
+                    &format!("{}  seed {}", format_timestamp(meta.timestamp), meta.seed),
+                );
+            }
+            None => {
+                ctx.print_color(
+                    8,
+                    y,
+                    fg,
+                    RGB::named(BLACK),
+                    &format!("{}: saved before slots existed", slot.name),
+                );
+            }
+        }
+        y += 2;
+    }
+
+    if let Some(message) = &gs.menu_message {
+        ctx.print_color_centered(37, RGB::named(RED), RGB::named(BLACK), message);
+    }
+
+    let count = gs.save_slots.len();
+    match ctx.key {
+        None => SlotMenuResult::NoSelection {
+            selected: selection,
+        },
+        Some(key) => match key {
+            VirtualKeyCode::Escape => SlotMenuResult::Cancel,
+            VirtualKeyCode::Up => SlotMenuResult::NoSelection {
+                selected: (selection + count - 1) % count,
+            },
+            VirtualKeyCode::Down => SlotMenuResult::NoSelection {
+                selected: (selection + 1) % count,
+            },
+            VirtualKeyCode::Return => SlotMenuResult::Load { slot: selection },
+            VirtualKeyCode::Delete | VirtualKeyCode::D => {
+                SlotMenuResult::Delete { slot: selection }
+            }
+            _ => SlotMenuResult::NoSelection {
+                selected: selection,
+            },
+        },
+    }
+}
+
+/// unix seconds to "YYYY-MM-DD HH:MM UTC", via Howard Hinnant's days-to-civil algorithm
+fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let hour = (timestamp % 86_400) / 3_600;
+    let minute = (timestamp % 3_600) / 60;
+
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year, month, day, hour, minute
+    )
+}
+
+/// how many rows the options menu has, the last being the way into the key bindings
+const OPTION_ROWS: usize = 7;
+pub const KEYBINDINGS_ROW: usize = OPTION_ROWS - 1;
+
+/// Edits the settings. Left and Right change the selected option, Escape closes the menu.
+/// Out of a game the main menu art stays behind it.
+pub fn options_menu(
+    gs: &mut State,
+    ctx: &mut BTerm,
+    selection: usize,
+    in_game: bool,
+) -> OptionsMenuResult {
+    if !in_game {
+        let assets = gs.ecs.fetch::<RexAssets>();
+        ctx.render_xp_sprite(&assets.menu, 0, 0);
+    }
+    let mut settings = gs.ecs.write_resource::<Settings>();
+    let selection = usize::min(selection, OPTION_ROWS - 1);
+
+    let on_off = |on: bool| (if on { "On" } else { "Off" }).to_string();
+    let particles = PARTICLE_DURATIONS
+        .iter()
+        .find(|(duration, _label)| *duration == settings.particle_duration)
+        .map_or(
+            format!("{}x", settings.particle_duration),
+            |(_duration, label)| label.to_string(),
+        );
+    let rows: [(&str, String); OPTION_ROWS] = [
+        ("Scanlines", on_off(settings.scanlines)),
+        ("Screen burn", on_off(settings.screen_burn)),
+        ("Particles", particles),
+        ("Message log", settings.log_verbosity.name().to_string()),
+        ("Auto-pickup", on_off(settings.auto_pickup)),
+        (
+            "Tile scale",
+            format!("{}x (after restart)", settings.tile_scale),
+        ),
+        ("Key bindings", "Enter".to_string()),
+    ];
+
+    ctx.draw_box_double(18, 14, 43, 14, RGB::named(WHEAT), RGB::named(BLACK));
+    ctx.print_color_centered(16, RGB::named(YELLOW), RGB::named(BLACK), "Options");
+    ctx.print_color_centered(
+        17,
+        RGB::named(GRAY),
+        RGB::named(BLACK),
+        "Left/Right: change   Escape: done",
+    );
+    let mut y = 19;
+    for (i, (name, value)) in rows.iter().enumerate() {
+        let fg = if i == selection {
+            RGB::named(MAGENTA)
+        } else {
+            RGB::named(WHITE)
+        };
+        ctx.print_color(21, y, fg, RGB::named(BLACK), name);
+        ctx.print_color(37, y, fg, RGB::named(BLACK), value);
+        y += 1;
+    }
+
+    match ctx.key {
+        None => OptionsMenuResult::NoSelection {
+            selected: selection,
+        },
+        Some(key) => match key {
+            VirtualKeyCode::Escape => OptionsMenuResult::Close,
+            VirtualKeyCode::Up => OptionsMenuResult::NoSelection {
+                selected: (selection + OPTION_ROWS - 1) % OPTION_ROWS,
+            },
+            VirtualKeyCode::Down => OptionsMenuResult::NoSelection {
+                selected: (selection + 1) % OPTION_ROWS,
+            },
+            VirtualKeyCode::Return if selection == KEYBINDINGS_ROW => {
+                OptionsMenuResult::Keybindings
+            }
+            VirtualKeyCode::Left => {
+                change_setting(&mut settings, selection, false);
+                OptionsMenuResult::NoSelection {
+                    selected: selection,
+                }
+            }
+            VirtualKeyCode::Right | VirtualKeyCode::Return => {
+                change_setting(&mut settings, selection, true);
+                OptionsMenuResult::NoSelection {
+                    selected: selection,
+                }
+            }
+            _ => OptionsMenuResult::NoSelection {
+                selected: selection,
+            },
+        },
+    }
+}
+
+/// steps the option on the given row of the options menu to its next or previous value
+fn change_setting(settings: &mut Settings, row: usize, forward: bool) {
+    match row {
+        0 => settings.scanlines = !settings.scanlines,
+        1 => settings.screen_burn = !settings.screen_burn,
+        2 => {
+            let current = PARTICLE_DURATIONS
+                .iter()
+                .position(|(duration, _label)| *duration == settings.particle_duration)
+                .unwrap_or(2);
+            settings.particle_duration =
+                PARTICLE_DURATIONS[step(current, PARTICLE_DURATIONS.len(), forward)].0;
+        }
+        3 => {
+            let current = LogVerbosity::ALL
+                .iter()
+                .position(|verbosity| *verbosity == settings.log_verbosity)
+                .unwrap_or(1);
+            settings.log_verbosity =
+                LogVerbosity::ALL[step(current, LogVerbosity::ALL.len(), forward)];
+        }
+        4 => settings.auto_pickup = !settings.auto_pickup,
+        5 => {
+            let scales = Settings::MAX_TILE_SCALE as usize;
+            settings.tile_scale =
+                step(settings.tile_scale as usize - 1, scales, forward) as i32 + 1;
+        }
+        _ => {}
+    }
+}
+
+/// the index after (or before) current in a list of count, wrapping round
+fn step(current: usize, count: usize, forward: bool) -> usize {
+    if forward {
+        (current + 1) % count
+    } else {
+        (current + count - 1) % count
+    }
+}
+
+/// Lists every command with the keys bound to it. Enter waits for a new key for the selected
+/// command, Delete clears its keys, 1-4 load a preset and Escape closes the screen.
+pub fn keymap_menu(
+    gs: &mut State,
+    ctx: &mut BTerm,
+    selection: usize,
+    capturing: bool,
+    in_game: bool,
+) -> KeymapMenuResult {
+    if !in_game {
+        let assets = gs.ecs.fetch::<RexAssets>();
+        ctx.render_xp_sprite(&assets.menu, 0, 0);
+    }
+    let mut keymap = gs.ecs.write_resource::<Keymap>();
+    let count = Command::ALL.len();
+    let selection = usize::min(selection, count - 1);
+
+    ctx.draw_box_double(10, 5, 59, 30, RGB::named(WHEAT), RGB::named(BLACK));
+    ctx.print_color_centered(7, RGB::named(YELLOW), RGB::named(BLACK), "Key Bindings");
+    let help = if capturing {
+        format!(
+            "Press a key for {} (Escape: cancel)",
+            Command::ALL[selection].name()
+        )
+    } else {
+        "Enter: add key   Delete: clear   Escape: done".to_string()
+    };
+    ctx.print_color_centered(8, RGB::named(GRAY), RGB::named(BLACK), &help);
+
+    let mut y = 10;
+    for (i, command) in Command::ALL.iter().enumerate() {
+        let fg = if i == selection {
+            RGB::named(MAGENTA)
+        } else {
+            RGB::named(WHITE)
+        };
+        let keys: Vec<String> = keymap
+            .keys_for(*command)
+            .into_iter()
+            .map(key_name)
+            .collect();
+        let keys = if keys.is_empty() {
+            "-".to_string()
+        } else {
+            keys.join(", ")
+        };
+        let keys: String = keys.chars().take(34).collect();
+        ctx.print_color(12, y, fg, RGB::named(BLACK), command.name());
+        ctx.print_color(32, y, fg, RGB::named(BLACK), &keys);
+        y += 1;
+    }
+    let presets: Vec<String> = Preset::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, preset)| format!("{}: {}", i + 1, preset.name()))
+        .collect();
+    ctx.print_color_centered(
+        y + 1,
+        RGB::named(GRAY),
+        RGB::named(BLACK),
+        &format!("Presets   {}", presets.join("   ")),
+    );
+
+    let key = match ctx.key {
+        None => {
+            return KeymapMenuResult::NoSelection {
+                selected: selection,
+                capturing,
+            }
+        }
+        Some(key) => key,
+    };
+    if capturing {
+        let bound = key != VirtualKeyCode::Escape
+            && is_bindable(key)
+            && keymap.bind(key, Command::ALL[selection]);
+        // keys that can't be bound, or are an essential command's last key, are ignored,
+        // so the screen keeps waiting
+        return KeymapMenuResult::NoSelection {
+            selected: selection,
+            capturing: !bound && key != VirtualKeyCode::Escape,
+        };
+    }
+
+    let preset = match key {
+        VirtualKeyCode::Key1 => Some(Preset::ALL[0]),
+        VirtualKeyCode::Key2 => Some(Preset::ALL[1]),
+        VirtualKeyCode::Key3 => Some(Preset::ALL[2]),
+        VirtualKeyCode::Key4 => Some(Preset::ALL[3]),
+        _ => None,
+    };
+    if let Some(preset) = preset {
+        *keymap = Keymap::preset(preset);
+    }
+    match key {
+        VirtualKeyCode::Escape => KeymapMenuResult::Close,
+        VirtualKeyCode::Up => KeymapMenuResult::NoSelection {
+            selected: (selection + count - 1) % count,
+            capturing: false,
+        },
+        VirtualKeyCode::Down => KeymapMenuResult::NoSelection {
+            selected: (selection + 1) % count,
+            capturing: false,
+        },
+        VirtualKeyCode::Return => KeymapMenuResult::NoSelection {
+            selected: selection,
+            capturing: true,
+        },
+        VirtualKeyCode::Delete | VirtualKeyCode::Back => {
+            // Options and Save and quit always keep a key
+            keymap.clear(Command::ALL[selection]);
+            KeymapMenuResult::NoSelection {
+                selected: selection,
+                capturing: false,
+            }
+        }
+        _ => KeymapMenuResult::NoSelection {
+            selected: selection,
+            capturing: false,
+        },
+    }
+}
@@ -0,0 +1,74 @@
+use super::rawmaster::RawRenderable;
+use crate::components::{EquipmentSlot, StatusKind};
+use serde::Deserialize;
+
+/// An item definition. Every effect is optional; an item gets a component for each one set.
+#[derive(Deserialize)]
+pub struct RawItem {
+    pub name: String,
+    pub renderable: Option<RawRenderable>,
+    /// a monster's spell or natural attack rather than a real item; it can't be picked up or dropped
+    #[serde(default)]
+    pub innate: bool,
+    /// in pounds; weightless if left out
+    pub weight: Option<f32>,
+    #[serde(default)]
+    pub stackable: bool,
+    #[serde(default)]
+    pub consumable: bool,
+    /// "scroll" or "potion": goes by a made-up name of that sort until identified
+    pub unidentified: Option<String>,
+    #[serde(default)]
+    pub identifies: bool,
+    pub ranged: Option<i32>,
+    pub damage: Option<i32>,
+    pub area_of_effect: Option<i32>,
+    pub healing: Option<i32>,
+    pub confusion: Option<i32>,
+    pub status: Option<RawStatus>,
+    #[serde(default)]
+    pub magic_mapping: bool,
+    #[serde(default)]
+    pub teleports: bool,
+    #[serde(default)]
+    pub food: bool,
+    pub equippable: Option<RawEquippable>,
+    pub launcher: Option<RawLauncher>,
+    pub ammo: Option<RawAmmo>,
+    pub thrown: Option<RawThrown>,
+}
+
+#[derive(Deserialize)]
+pub struct RawStatus {
+    pub kind: StatusKind,
+    pub turns: i32,
+    #[serde(default)]
+    pub potency: i32,
+}
+
+#[derive(Deserialize)]
+pub struct RawEquippable {
+    pub slot: EquipmentSlot,
+    #[serde(default)]
+    pub two_handed: bool,
+    pub power_bonus: Option<i32>,
+    pub defense_bonus: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct RawLauncher {
+    pub range: i32,
+    pub ammo: String,
+}
+
+#[derive(Deserialize)]
+pub struct RawAmmo {
+    pub kind: String,
+    pub damage: i32,
+}
+
+#[derive(Deserialize)]
+pub struct RawThrown {
+    pub range: i32,
+    pub damage: i32,
+}
@@ -0,0 +1,41 @@
+use super::rawmaster::RawRenderable;
+use serde::Deserialize;
+
+/// A monster definition
+#[derive(Deserialize)]
+pub struct RawMob {
+    pub name: String,
+    pub renderable: Option<RawRenderable>,
+    #[serde(default)]
+    pub blocks_tile: bool,
+    pub stats: RawMobStats,
+    pub vision_range: i32,
+    /// defaults to the player's speed
+    pub speed: Option<i32>,
+    #[serde(default)]
+    pub xp: i32,
+    pub ai: Option<RawAi>,
+    /// items spawned in the mob's pack: innate abilities, or loot dropped when it dies
+    #[serde(default)]
+    pub inventory: Vec<String>,
+}
+
+/// AI tuning, see AiState
+#[derive(Deserialize)]
+pub struct RawAi {
+    #[serde(default)]
+    pub wanders: bool,
+    pub search_turns: i32,
+    #[serde(default)]
+    pub flee_below: f32,
+    #[serde(default)]
+    pub uses_items: bool,
+}
+
+#[derive(Deserialize)]
+pub struct RawMobStats {
+    pub max_hp: i32,
+    pub hp: i32,
+    pub defense: i32,
+    pub power: i32,
+}
@@ -0,0 +1,60 @@
+// module for data-driven entity definitions
+mod item_structs;
+mod mob_structs;
+mod prop_structs;
+mod rawmaster;
+mod spawn_table_structs;
+
+pub use rawmaster::*;
+
+use bracket_lib::prelude::console;
+use item_structs::RawItem;
+use mob_structs::RawMob;
+use prop_structs::RawProp;
+use serde::Deserialize;
+use spawn_table_structs::RawSpawnEntry;
+use std::sync::OnceLock;
+
+const RAWS_PATH: &str = "./raws/spawns.json";
+const EMBEDDED_RAWS: &str = include_str!("../../raws/spawns.json");
+
+/// Everything in a raws file, exactly as it comes out of the JSON
+#[derive(Deserialize)]
+pub struct Raws {
+    pub items: Vec<RawItem>,
+    pub mobs: Vec<RawMob>,
+    pub props: Vec<RawProp>,
+    pub spawn_table: Vec<RawSpawnEntry>,
+}
+
+static RAWS: OnceLock<RawMaster> = OnceLock::new();
+
+/// the loaded definitions, read in on first use
+pub fn raws() -> &'static RawMaster {
+    RAWS.get_or_init(|| RawMaster::load(read_raws()))
+}
+
+/// An on-disk raws/spawns.json wins, so content can be changed without a rebuild.
+/// Otherwise falls back to the copy baked into the binary.
+fn read_raws() -> Raws {
+    if let Some(data) = read_raws_file() {
+        match serde_json::from_str(&data) {
+            Ok(raws) => return raws,
+            Err(e) => console::log(format!(
+                "Unable to parse {}: {}. Using built-in raws.",
+                RAWS_PATH, e
+            )),
+        }
+    }
+    serde_json::from_str(EMBEDDED_RAWS).expect("Unable to parse built-in raws")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_raws_file() -> Option<String> {
+    std::fs::read_to_string(RAWS_PATH).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_raws_file() -> Option<String> {
+    None
+}
@@ -0,0 +1,20 @@
+use super::rawmaster::RawRenderable;
+use serde::Deserialize;
+
+/// A prop definition: traps and anything else that sits on the map without being picked up
+#[derive(Deserialize)]
+pub struct RawProp {
+    pub name: String,
+    pub renderable: Option<RawRenderable>,
+    #[serde(default)]
+    pub hidden: bool,
+    pub entry_trigger: Option<RawEntryTrigger>,
+    #[serde(default)]
+    pub single_activation: bool,
+}
+
+/// what happens to whatever steps on the prop
+#[derive(Deserialize)]
+pub struct RawEntryTrigger {
+    pub damage: Option<i32>,
+}
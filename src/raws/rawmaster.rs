@@ -0,0 +1,357 @@
+use super::{item_structs::RawItem, mob_structs::RawMob, prop_structs::RawProp, Raws};
+use crate::{components::*, systems::spawn_table::RandomTable};
+use bracket_lib::prelude::*;
+use serde::Deserialize;
+use specs::{
+    prelude::*,
+    saveload::{MarkedBuilder, SimpleMarker},
+};
+use std::collections::HashMap;
+
+/// How an entity is drawn. Colors are html-style hex strings, e.g. "#FF0000".
+#[derive(Deserialize)]
+pub struct RawRenderable {
+    pub glyph: String,
+    pub fg: String,
+    pub bg: String,
+    pub order: i32,
+}
+
+/// Where a spawned entity ends up
+pub enum SpawnType {
+    AtPosition {
+        x: i32,
+        y: i32,
+    },
+    /// in the pack of a mob or the player; only items can be carried
+    Carried {
+        by: Entity,
+    },
+}
+
+/// The loaded raws, indexed by name for spawning
+pub struct RawMaster {
+    raws: Raws,
+    item_index: HashMap<String, usize>,
+    mob_index: HashMap<String, usize>,
+    prop_index: HashMap<String, usize>,
+}
+
+impl RawMaster {
+    /// indexes the raws and warns about names that are defined twice or spawned but never defined
+    pub fn load(raws: Raws) -> RawMaster {
+        let mut used_names: Vec<&str> = Vec::new();
+        let mut item_index = HashMap::new();
+        for (i, item) in raws.items.iter().enumerate() {
+            warn_if_duplicate(&mut used_names, &item.name);
+            item_index.insert(item.name.clone(), i);
+        }
+        let mut mob_index = HashMap::new();
+        for (i, mob) in raws.mobs.iter().enumerate() {
+            warn_if_duplicate(&mut used_names, &mob.name);
+            mob_index.insert(mob.name.clone(), i);
+        }
+        let mut prop_index = HashMap::new();
+        for (i, prop) in raws.props.iter().enumerate() {
+            warn_if_duplicate(&mut used_names, &prop.name);
+            prop_index.insert(prop.name.clone(), i);
+        }
+
+        for spawn in raws.spawn_table.iter() {
+            if !used_names.contains(&spawn.name.as_str()) {
+                console::log(format!(
+                    "WARNING: spawn table references unknown entity {}",
+                    spawn.name
+                ));
+            }
+        }
+
+        RawMaster {
+            raws,
+            item_index,
+            mob_index,
+            prop_index,
+        }
+    }
+}
+
+impl RawMaster {
+    /// the real name and kind of every item that goes unidentified
+    pub fn unidentified_items(&self) -> Vec<(String, String)> {
+        self.raws
+            .items
+            .iter()
+            .filter_map(|item| {
+                item.unidentified
+                    .as_ref()
+                    .map(|kind| (item.name.clone(), kind.clone()))
+            })
+            .collect()
+    }
+}
+
+fn warn_if_duplicate<'a>(used_names: &mut Vec<&'a str>, name: &'a str) {
+    if used_names.contains(&name) {
+        console::log(format!("WARNING: duplicate entity name in raws: {}", name));
+    }
+    used_names.push(name);
+}
+
+/// builds the weighted spawn table for a depth from every entry whose depth range covers it
+pub fn get_spawn_table_for_depth(raws: &RawMaster, depth: i32) -> RandomTable {
+    let mut table = RandomTable::new();
+    for entry in raws
+        .raws
+        .spawn_table
+        .iter()
+        .filter(|entry| depth >= entry.min_depth && depth <= entry.max_depth)
+    {
+        let mut weight = entry.weight;
+        if entry.add_map_depth_to_weight {
+            weight += depth;
+        }
+        table = table.add(entry.name.clone(), weight);
+    }
+    table
+}
+
+/// Spawns the item, mob or prop with the given name.
+/// Returns None if no definition has that name, or it's a mob or prop being carried.
+pub fn spawn_named_entity(
+    raws: &RawMaster,
+    world: &mut World,
+    key: &str,
+    spawn: SpawnType,
+) -> Option<Entity> {
+    if let Some(idx) = raws.item_index.get(key) {
+        return Some(spawn_item(&raws.raws.items[*idx], world, spawn));
+    }
+    let (x, y) = match spawn {
+        SpawnType::AtPosition { x, y } => (x, y),
+        SpawnType::Carried { .. } => {
+            console::log(format!("WARNING: {} can't be carried", key));
+            return None;
+        }
+    };
+    if let Some(idx) = raws.mob_index.get(key) {
+        return Some(spawn_mob(raws, &raws.raws.mobs[*idx], world, x, y));
+    }
+    if let Some(idx) = raws.prop_index.get(key) {
+        return Some(spawn_prop(&raws.raws.props[*idx], world, x, y));
+    }
+    None
+}
+
+fn get_renderable_component(renderable: &RawRenderable) -> Renderable {
+    Renderable {
+        glyph: to_cp437(renderable.glyph.chars().next().unwrap_or('?')),
+        fg: RGB::from_hex(&renderable.fg).unwrap_or_else(|_| RGB::named(WHITE)),
+        bg: RGB::from_hex(&renderable.bg).unwrap_or_else(|_| RGB::named(BLACK)),
+        render_order: renderable.order,
+    }
+}
+
+fn spawn_item(item: &RawItem, world: &mut World, spawn: SpawnType) -> Entity {
+    let enchantment = match &item.equippable {
+        Some(_) if !item.innate => {
+            roll_enchantment(&mut world.write_resource::<RandomNumberGenerator>())
+        }
+        _ => 0,
+    };
+    let mut eb = world.create_entity().with(Name {
+        name: item.name.clone(),
+    });
+    match spawn {
+        SpawnType::AtPosition { x, y } => eb = eb.with(Position { x, y }),
+        SpawnType::Carried { by } => eb = eb.with(InBackpack { owner: by }),
+    }
+
+    if !item.innate {
+        eb = eb.with(Item {});
+    }
+    if let Some(renderable) = &item.renderable {
+        eb = eb.with(get_renderable_component(renderable));
+    }
+    if item.consumable {
+        eb = eb.with(Consumable {});
+    }
+    if let Some(weight) = item.weight {
+        eb = eb.with(Weight { weight });
+    }
+    if item.stackable {
+        eb = eb.with(Stackable { quantity: 1 });
+    }
+    if let Some(range) = item.ranged {
+        eb = eb.with(Ranged { range });
+    }
+    if let Some(damage) = item.damage {
+        eb = eb.with(InflictsDamage { damage });
+    }
+    if let Some(radius) = item.area_of_effect {
+        eb = eb.with(AreaOfEffect { radius });
+    }
+    if let Some(restore_hp) = item.healing {
+        eb = eb.with(ProvidesHealing { restore_hp });
+    }
+    if let Some(turns) = item.confusion {
+        eb = eb.with(Confusion { turns });
+    }
+    if let Some(status) = &item.status {
+        eb = eb.with(InflictsStatus {
+            kind: status.kind,
+            turns: status.turns,
+            potency: status.potency,
+        });
+    }
+    if item.magic_mapping {
+        eb = eb.with(MagicMapper {});
+    }
+    if item.teleports {
+        eb = eb.with(Teleports {});
+    }
+    if item.food {
+        eb = eb.with(ProvidesFood {});
+    }
+    if let Some(equippable) = &item.equippable {
+        eb = eb.with(Equippable {
+            slot: equippable.slot,
+            two_handed: equippable.two_handed,
+        });
+        // enchantments improve whatever the item is already good for
+        let power_bonus = equippable.power_bonus.map(|power| power + enchantment);
+        let defense_bonus = match power_bonus {
+            Some(_) => equippable.defense_bonus,
+            None => equippable
+                .defense_bonus
+                .map(|defense| defense + enchantment),
+        };
+        if let Some(power) = power_bonus {
+            eb = eb.with(MeleePowerBonus { power });
+        }
+        if let Some(defense) = defense_bonus {
+            eb = eb.with(DefenseBonus { defense });
+        }
+        if enchantment != 0 && (power_bonus.is_some() || defense_bonus.is_some()) {
+            eb = eb.with(Enchantment { bonus: enchantment });
+            eb = eb.with(UnknownEnchantment {});
+            if enchantment < 0 {
+                eb = eb.with(Cursed {});
+            }
+        }
+    }
+    if item.identifies {
+        eb = eb.with(Identifies {});
+    }
+
+    if let Some(launcher) = &item.launcher {
+        eb = eb.with(Launcher {
+            range: launcher.range,
+            ammo: launcher.ammo.clone(),
+        });
+    }
+    if let Some(ammo) = &item.ammo {
+        eb = eb.with(Ammunition {
+            kind: ammo.kind.clone(),
+            damage: ammo.damage,
+        });
+    }
+    if let Some(thrown) = &item.thrown {
+        eb = eb.with(Thrown {
+            range: thrown.range,
+            damage: thrown.damage,
+        });
+    }
+
+    eb.marked::<SimpleMarker<SerializeMe>>().build()
+}
+
+/// Most equipment is plain. One piece in ten is cursed with a negative bonus,
+/// and one in five is enchanted.
+fn roll_enchantment(rng: &mut RandomNumberGenerator) -> i32 {
+    match rng.roll_dice(1, 20) {
+        1 => -2,
+        2 => -1,
+        17..=19 => 1,
+        20 => 2,
+        _ => 0,
+    }
+}
+
+fn spawn_mob(raws: &RawMaster, mob: &RawMob, world: &mut World, x: i32, y: i32) -> Entity {
+    let mut eb = world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: mob.name.clone(),
+        })
+        .with(Monster {})
+        .with(Viewshed {
+            visible_tiles: Vec::new(),
+            range: mob.vision_range,
+            dirty: true,
+        })
+        .with(CombatStats {
+            max_hp: mob.stats.max_hp,
+            hp: mob.stats.hp,
+            defense: mob.stats.defense,
+            power: mob.stats.power,
+        })
+        .with(Initiative {
+            speed: mob.speed.unwrap_or(NORMAL_SPEED),
+            energy: 0,
+        });
+
+    if let Some(renderable) = &mob.renderable {
+        eb = eb.with(get_renderable_component(renderable));
+    }
+    if mob.blocks_tile {
+        eb = eb.with(BlocksTile {});
+    }
+    if mob.xp > 0 {
+        eb = eb.with(ProvidesXp { xp: mob.xp });
+    }
+    let mut ai = AiState::default();
+    if let Some(raw_ai) = &mob.ai {
+        ai.wanders = raw_ai.wanders;
+        ai.search_turns = raw_ai.search_turns;
+        ai.flee_below = raw_ai.flee_below;
+        ai.uses_items = raw_ai.uses_items;
+    }
+    ai.mode = ai.resting_mode();
+    eb = eb.with(ai);
+
+    let entity = eb.marked::<SimpleMarker<SerializeMe>>().build();
+    for name in mob.inventory.iter() {
+        if spawn_named_entity(raws, world, name, SpawnType::Carried { by: entity }).is_none() {
+            console::log(format!(
+                "WARNING: {} carries unknown item {}",
+                mob.name, name
+            ));
+        }
+    }
+    entity
+}
+
+fn spawn_prop(prop: &RawProp, world: &mut World, x: i32, y: i32) -> Entity {
+    let mut eb = world.create_entity().with(Position { x, y }).with(Name {
+        name: prop.name.clone(),
+    });
+
+    if let Some(renderable) = &prop.renderable {
+        eb = eb.with(get_renderable_component(renderable));
+    }
+    if prop.hidden {
+        eb = eb.with(Hidden {});
+    }
+    if let Some(trigger) = &prop.entry_trigger {
+        eb = eb.with(EntryTrigger {});
+        if let Some(damage) = trigger.damage {
+            eb = eb.with(InflictsDamage { damage });
+        }
+    }
+    if prop.single_activation {
+        eb = eb.with(SingleActivation {});
+    }
+
+    eb.marked::<SimpleMarker<SerializeMe>>().build()
+}
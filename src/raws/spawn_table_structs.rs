@@ -0,0 +1,12 @@
+use serde::Deserialize;
+
+/// One line of the spawn table. The name must match an item, mob or prop.
+#[derive(Deserialize)]
+pub struct RawSpawnEntry {
+    pub name: String,
+    pub weight: i32,
+    pub min_depth: i32,
+    pub max_depth: i32,
+    #[serde(default)]
+    pub add_map_depth_to_weight: bool,
+}
@@ -0,0 +1,62 @@
+// module for seeded randomness
+use bracket_lib::prelude::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
+
+/// Which part of the game an RNG stream belongs to.
+/// Each gets its own stream so that, say, an extra combat roll can't change the next level's layout.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RngStream {
+    MapGen,
+    Spawn,
+    Combat,
+    /// the made-up names of unidentified items, rolled once per run
+    ItemNames,
+}
+
+/// The master seed for a run. Every random number in the game is derived from it,
+/// so replaying a seed rebuilds the same dungeon and spawns.
+#[derive(Copy, Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct RunSeed {
+    pub seed: u64,
+}
+
+impl RunSeed {
+    pub fn new(seed: u64) -> RunSeed {
+        RunSeed { seed }
+    }
+
+    /// rolls a fresh seed for a new run
+    pub fn random() -> RunSeed {
+        RunSeed {
+            seed: RandomNumberGenerator::new().next_u64(),
+        }
+    }
+
+    /// reads `--seed <number>` from the command line, if it was given
+    pub fn from_args() -> Option<RunSeed> {
+        std::env::args()
+            .skip_while(|arg| arg != "--seed")
+            .nth(1)
+            .and_then(|seed| seed.parse::<u64>().ok())
+            .map(RunSeed::new)
+    }
+
+    /// the seed for the next run: the one from `--seed`, so it can be replayed, or a fresh one
+    pub fn for_next_run() -> RunSeed {
+        RunSeed::from_args().unwrap_or_else(RunSeed::random)
+    }
+
+    /// builds the RNG for one stream on one level
+    pub fn stream(&self, depth: i32, stream: RngStream) -> RandomNumberGenerator {
+        let salt = ((depth as u32 as u64) << 8) | stream as u64;
+        RandomNumberGenerator::seeded(splitmix64(self.seed ^ splitmix64(salt)))
+    }
+}
+
+/// splitmix64 finalizer, spreads nearby inputs (depth 1, depth 2...) across the whole u64 range
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
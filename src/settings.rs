@@ -0,0 +1,121 @@
+// module for the player's game options, kept in the config directory between runs
+use crate::config::config_dir;
+use bracket_lib::prelude::console;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// How much the message log says
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LogVerbosity {
+    /// leaves out attacks that do no damage
+    Brief,
+    Normal,
+    /// adds the numbers behind every hit
+    Verbose,
+}
+
+impl LogVerbosity {
+    pub const ALL: [LogVerbosity; 3] = [
+        LogVerbosity::Brief,
+        LogVerbosity::Normal,
+        LogVerbosity::Verbose,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LogVerbosity::Brief => "Brief",
+            LogVerbosity::Normal => "Normal",
+            LogVerbosity::Verbose => "Verbose",
+        }
+    }
+}
+
+/// the particle durations the options menu steps through, as a multiple of the usual
+pub const PARTICLE_DURATIONS: [(f32, &str); 4] =
+    [(0.0, "Off"), (0.5, "Short"), (1.0, "Normal"), (2.0, "Long")];
+
+/// Options the player picks from the Options menu. Fields missing from the file keep their
+/// defaults, so older settings files still load.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+    pub scanlines: bool,
+    /// only shows with scanlines on
+    pub screen_burn: bool,
+    /// particles last this many times as long as usual; 0 turns them off
+    pub particle_duration: f32,
+    pub log_verbosity: LogVerbosity,
+    /// picks up an item when the player steps onto it
+    pub auto_pickup: bool,
+    /// screen pixels per font pixel; the window is only resized on the next start
+    pub tile_scale: i32,
+    /// not in the options menu, but can be changed in the settings file; read on start
+    pub window_title: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            scanlines: true,
+            screen_burn: true,
+            particle_duration: 1.0,
+            log_verbosity: LogVerbosity::Normal,
+            auto_pickup: false,
+            tile_scale: 1,
+            window_title: "Khazad-dum".to_string(),
+        }
+    }
+}
+
+impl Settings {
+    pub const MAX_TILE_SCALE: i32 = 3;
+
+    /// Reads the settings file, falling back to the defaults if it's missing or unreadable.
+    pub fn load() -> Settings {
+        let data = match read_settings_file() {
+            Some(data) => data,
+            None => return Settings::default(),
+        };
+        match serde_json::from_str::<Settings>(&data) {
+            Ok(mut settings) => {
+                settings.tile_scale = i32::clamp(settings.tile_scale, 1, Settings::MAX_TILE_SCALE);
+                settings.particle_duration = f32::max(0.0, settings.particle_duration);
+                settings
+            }
+            Err(e) => {
+                console::log(format!(
+                    "Unable to parse {}: {}. Using the default settings.",
+                    settings_path().display(),
+                    e
+                ));
+                Settings::default()
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self) -> std::io::Result<()> {
+        std::fs::create_dir_all(config_dir())?;
+        let data = serde_json::to_string_pretty(self)?;
+        std::fs::write(settings_path(), data)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn settings_path() -> PathBuf {
+    config_dir().join("settings.json")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_settings_file() -> Option<String> {
+    std::fs::read_to_string(settings_path()).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_settings_file() -> Option<String> {
+    None
+}
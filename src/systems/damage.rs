@@ -1,38 +1,11 @@
 use crate::{
-    components::{CombatStats, Name, Player, SufferDamage},
-    entities,
-    gamelog::GameLog,
-    Map, Position,
+    components::{CombatStats, InBackpack, Item, Name, Player},
+    gamelog::{GameLog, LogCategory},
+    Position,
 };
+use bracket_lib::prelude::{RED, RGB};
 use specs::prelude::*;
 
-pub struct DamageSystem {}
-
-impl<'a> System<'a> for DamageSystem {
-    type SystemData = (
-        WriteStorage<'a, CombatStats>,
-        WriteStorage<'a, SufferDamage>,
-        ReadStorage<'a, Position>,
-        WriteExpect<'a, Map>,
-        Entities<'a>,
-    );
-
-    fn run(&mut self, data: Self::SystemData) {
-        let (mut stats, mut damage, positions, mut map, entities) = data;
-
-        for (entity, mut stats, damage) in (&entities, &mut stats, &damage).join() {
-            stats.hp -= damage.amount.iter().sum::<i32>();
-            let pos = positions.get(entity);
-            if let Some(pos) = pos {
-                let idx = map.xy_idx(pos.x, pos.y);
-                map.bloodstains.insert(idx);
-            }
-        }
-
-        damage.clear();
-    }
-}
-
 /// delete dead entities
 pub fn delete_dead(ecs: &mut World) {
     let mut dead: Vec<Entity> = Vec::new();
@@ -55,17 +28,51 @@ pub fn delete_dead(ecs: &mut World) {
                     None => {
                         let victim_name = names.get(entity);
                         if let Some(victim_name) = victim_name {
-                            log.entries.push(format!("{} has died.", victim_name.name));
+                            log.add(
+                                LogCategory::Combat,
+                                format!("{} has died.", victim_name.name),
+                            );
                         }
                         dead.push(entity)
                     }
                     Some(_) => {
-                        log.entries.push(format!("You died"));
+                        log.add_colored(LogCategory::Combat, format!("You died"), RGB::named(RED));
                     }
                 }
             }
         }
     }
+    // whatever the dead were carrying falls to the floor, their innate abilities go with them
+    let mut carried: Vec<Entity> = Vec::new();
+    {
+        let entities = ecs.entities();
+        let items = ecs.read_storage::<Item>();
+        let mut backpack = ecs.write_storage::<InBackpack>();
+        let mut positions = ecs.write_storage::<Position>();
+        for (entity, pack) in (&entities, &backpack).join() {
+            if !dead.contains(&pack.owner) {
+                continue;
+            }
+            if items.get(entity).is_some() {
+                if let Some(pos) = positions.get(pack.owner).cloned() {
+                    positions.insert(entity, pos).expect("Unable to drop item");
+                }
+            }
+            carried.push(entity);
+        }
+        for entity in carried.iter() {
+            backpack.remove(*entity);
+        }
+    }
+    {
+        let items = ecs.read_storage::<Item>();
+        dead.extend(
+            carried
+                .into_iter()
+                .filter(|entity| items.get(*entity).is_none()),
+        );
+    }
+
     // deletes all entities in the dead vector
     for victim in dead {
         ecs.delete_entity(victim).expect("unable to delete");
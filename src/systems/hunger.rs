@@ -1,4 +1,8 @@
-use crate::{gamelog::GameLog, HungerClock, HungerState, RunState, SufferDamage};
+use crate::{
+    effects::{EffectQueue, EffectType, Targets},
+    gamelog::{GameLog, LogCategory},
+    HungerClock, HungerState, MyTurn,
+};
 use specs::prelude::*;
 
 pub struct HungerSystem {}
@@ -6,66 +10,55 @@ pub struct HungerSystem {}
 impl<'a> System<'a> for HungerSystem {
     type SystemData = (
         ReadExpect<'a, Entity>,
-        ReadExpect<'a, RunState>,
         Entities<'a>,
         WriteStorage<'a, HungerClock>,
-        WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, MyTurn>,
+        WriteExpect<'a, EffectQueue>,
         WriteExpect<'a, GameLog>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (player_entity, runstate, entities, mut hunger_clock, mut suffer_damage, mut log) =
-            data;
+        let (player_entity, entities, mut hunger_clock, turns, mut effects, mut log) = data;
 
-        for (entity, mut clock) in (&entities, &mut hunger_clock).join() {
-            let mut proceed = false;
-
-            match *runstate {
-                RunState::PlayerTurn => {
-                    if entity == *player_entity {
-                        proceed = true;
-                    }
-                }
-                RunState::MonsterTurn => {
-                    if entity != *player_entity {
-                        proceed = true;
-                    }
-                }
-                _ => proceed = false,
-            }
-
-            if proceed {
-                clock.duration -= 1;
-                if clock.duration < 1 {
-                    match clock.state {
-                        HungerState::WellFed => {
-                            clock.state = HungerState::Normal;
-                            clock.duration = 50;
-                            if entity == *player_entity {
-                                log.entries.push("You are no longer well fed.".to_string());
-                            }
+        // hunger only grows on the turns of whoever is hungry
+        for (entity, mut clock, _turn) in (&entities, &mut hunger_clock, &turns).join() {
+            clock.duration -= 1;
+            if clock.duration < 1 {
+                match clock.state {
+                    HungerState::WellFed => {
+                        clock.state = HungerState::Normal;
+                        clock.duration = 50;
+                        if entity == *player_entity {
+                            log.add(
+                                LogCategory::Hunger,
+                                "You are no longer well fed.".to_string(),
+                            );
                         }
-                        HungerState::Normal => {
-                            clock.state = HungerState::Hungry;
-                            clock.duration = 50;
-                            if entity == *player_entity {
-                                log.entries.push("You are hungry.".to_string());
-                            }
+                    }
+                    HungerState::Normal => {
+                        clock.state = HungerState::Hungry;
+                        clock.duration = 50;
+                        if entity == *player_entity {
+                            log.add(LogCategory::Hunger, "You are hungry.".to_string());
                         }
-                        HungerState::Hungry => {
-                            clock.state = HungerState::Starving;
-                            clock.duration = 50;
+                    }
+                    HungerState::Hungry => {
+                        clock.state = HungerState::Starving;
+                        clock.duration = 50;
 
-                            if entity == *player_entity {
-                                log.entries.push("You are starving!".to_string());
-                            }
+                        if entity == *player_entity {
+                            log.add(LogCategory::Hunger, "You are starving!".to_string());
                         }
-                        HungerState::Starving => {
-                            if entity == *player_entity {
-                                log.entries.push("You need to eat!".to_string());
-                            }
-                            SufferDamage::new_damage(&mut suffer_damage, entity, 1);
+                    }
+                    HungerState::Starving => {
+                        if entity == *player_entity {
+                            log.add(LogCategory::Hunger, "You need to eat!".to_string());
                         }
+                        effects.add_effect(
+                            None,
+                            EffectType::Damage { amount: 1 },
+                            Targets::Single { target: entity },
+                        );
                     }
                 }
             }
@@ -0,0 +1,96 @@
+use super::inventory::{burden, carried_weight, carry_capacity, Burden};
+use crate::{
+    CombatStats, Equipped, InBackpack, Initiative, MyTurn, Position, RunState, Stackable,
+    StatusEffects, Weight, TURN_ENERGY,
+};
+use specs::prelude::*;
+
+/// Hands out turns. Every tick each creature on the level gains energy by its speed,
+/// and those with enough for a turn get MyTurn. Once the player has enough the clock
+/// stops and the game waits for input.
+pub struct InitiativeSystem {}
+
+impl<'a> System<'a> for InitiativeSystem {
+    type SystemData = (
+        WriteExpect<'a, RunState>,
+        ReadExpect<'a, Entity>,
+        Entities<'a>,
+        WriteStorage<'a, Initiative>,
+        WriteStorage<'a, MyTurn>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, StatusEffects>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Weight>,
+        ReadStorage<'a, Stackable>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut runstate,
+            player_entity,
+            entities,
+            mut initiatives,
+            mut turns,
+            positions,
+            combat_stats,
+            statuses,
+            backpack,
+            equipped,
+            weights,
+            stackables,
+        ) = data;
+
+        // last tick's turns are over, whoever they belonged to
+        turns.clear();
+
+        match *runstate {
+            RunState::PlayerTurn => {
+                turns
+                    .insert(*player_entity, MyTurn {})
+                    .expect("Unable to insert turn");
+                return;
+            }
+            RunState::Ticking => {}
+            _ => return,
+        }
+
+        // creatures from saves made before initiative existed move at normal speed
+        let missing: Vec<Entity> = (&entities, &combat_stats, !&initiatives)
+            .join()
+            .map(|(entity, _stats, _no_initiative)| entity)
+            .collect();
+        for entity in missing {
+            initiatives
+                .insert(entity, Initiative::default())
+                .expect("Unable to insert initiative");
+        }
+
+        // only what's on the current level takes turns
+        for (entity, initiative, _pos) in (&entities, &mut initiatives, &positions).join() {
+            let mut speed = statuses.get(entity).map_or(initiative.speed, |status| {
+                status.adjust_speed(initiative.speed)
+            });
+            let carried = carried_weight(entity, &backpack, &equipped, &weights, &stackables);
+            let capacity = combat_stats.get(entity).map_or(0.0, carry_capacity);
+            if burden(carried, capacity) == Burden::Burdened {
+                speed /= 2;
+            }
+            initiative.energy += i32::max(1, speed);
+            if initiative.energy < TURN_ENERGY {
+                continue;
+            }
+            initiative.energy -= TURN_ENERGY;
+
+            if entity == *player_entity {
+                // the player's turn starts when they act, in the PlayerTurn pass
+                *runstate = RunState::AwaitingInput;
+            } else {
+                turns
+                    .insert(entity, MyTurn {})
+                    .expect("Unable to insert turn");
+            }
+        }
+    }
+}
@@ -1,41 +1,89 @@
-use crate::{components::*, gamelog::GameLog, Map, Point, RunState};
-use bracket_lib::prelude::*;
-use specs::prelude::*;
+use crate::{
+    components::*,
+    effects::{EffectQueue, EffectType, Targets},
+    gamelog::{GameLog, LogCategory},
+    identification::MagicItemNames,
+    Map, Point,
+};
+use specs::{
+    prelude::*,
+    saveload::{MarkedBuilder, SimpleMarker},
+};
 
-use super::particle_system::ParticleBuilder;
 pub struct ItemCollectionSystem {}
 
 impl<'a> System<'a> for ItemCollectionSystem {
     type SystemData = (
         ReadExpect<'a, Entity>,
         WriteExpect<'a, GameLog>,
+        Entities<'a>,
         WriteStorage<'a, WantsToPickupItem>,
         WriteStorage<'a, Position>,
         ReadStorage<'a, Name>,
         WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Stackable>,
+        ReadExpect<'a, MagicItemNames>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (player_entity, mut gamelog, mut wants_pickup, mut positions, names, mut backpack) =
-            data;
+        let (
+            player_entity,
+            mut gamelog,
+            entities,
+            mut wants_pickup,
+            mut positions,
+            names,
+            mut backpack,
+            mut stackables,
+            magic_items,
+        ) = data;
 
         for pickup in wants_pickup.join() {
+            let name = names.get(pickup.item).unwrap().name.clone();
             positions.remove(pickup.item);
-            backpack
-                .insert(
-                    pickup.item,
-                    InBackpack {
-                        owner: pickup.acquired_by,
-                    },
-                )
-                .expect("Unable to add to backpack");
-
             if pickup.acquired_by == *player_entity {
-                gamelog.entries.push(format!(
-                    "You pick up the {}.",
-                    names.get(pickup.item).unwrap().name
-                ));
+                let quantity = stackables
+                    .get(pickup.item)
+                    .map_or(1, |stack| stack.quantity);
+                let shown = if quantity > 1 {
+                    format!("{} (x{})", magic_items.display(&name), quantity)
+                } else {
+                    magic_items.display(&name)
+                };
+                gamelog.add(LogCategory::Item, format!("You pick up the {}.", shown));
+            }
+
+            // a stackable item joins a stack of the same thing already in the pack
+            let stack = match stackables.get(pickup.item) {
+                Some(_) => (&entities, &backpack, &stackables, &names)
+                    .join()
+                    .find(|(entity, pack, _stack, stack_name)| {
+                        *entity != pickup.item
+                            && pack.owner == pickup.acquired_by
+                            && stack_name.name == name
+                    })
+                    .map(|(entity, _pack, _stack, _name)| entity),
+                None => None,
             };
+            match stack {
+                Some(stack) => {
+                    let quantity = stackables.get(pickup.item).map_or(1, |item| item.quantity);
+                    if let Some(stack) = stackables.get_mut(stack) {
+                        stack.quantity += quantity;
+                    }
+                    entities.delete(pickup.item).expect("Unable to merge stack");
+                }
+                None => {
+                    backpack
+                        .insert(
+                            pickup.item,
+                            InBackpack {
+                                owner: pickup.acquired_by,
+                            },
+                        )
+                        .expect("Unable to add to backpack");
+                }
+            }
         }
         wants_pickup.clear();
     }
@@ -50,23 +98,14 @@ impl<'a> System<'a> for ItemUseSystem {
         Entities<'a>,
         WriteStorage<'a, WantsToUseItem>,
         ReadStorage<'a, Name>,
-        ReadStorage<'a, ProvidesHealing>,
-        WriteStorage<'a, CombatStats>,
-        ReadStorage<'a, Consumable>,
-        ReadStorage<'a, InflictsDamage>,
-        WriteExpect<'a, Map>,
-        WriteStorage<'a, SufferDamage>,
+        ReadExpect<'a, Map>,
         ReadStorage<'a, AreaOfEffect>,
-        WriteStorage<'a, Confusion>,
         ReadStorage<'a, Equippable>,
         WriteStorage<'a, Equipped>,
         WriteStorage<'a, InBackpack>,
-        WriteExpect<'a, ParticleBuilder>,
-        ReadStorage<'a, Position>,
-        ReadStorage<'a, ProvidesFood>,
-        WriteStorage<'a, HungerClock>,
-        ReadStorage<'a, MagicMapper>,
-        WriteExpect<'a, RunState>,
+        WriteExpect<'a, EffectQueue>,
+        ReadStorage<'a, Cursed>,
+        WriteStorage<'a, UnknownEnchantment>,
     );
     fn run(&mut self, data: Self::SystemData) {
         let (
@@ -75,264 +114,136 @@ impl<'a> System<'a> for ItemUseSystem {
             entities,
             mut wants_use,
             names,
-            healing,
-            mut combat_stats,
-            consumables,
-            inflict_damage,
-            mut map,
-            mut suffer_damage,
+            map,
             aoe,
-            mut confuse,
             equippable,
             mut equipped,
             mut backpack,
-            mut particle_builder,
-            positions,
-            edible,
-            mut hunger_clocks,
-            magic_mapper,
-            mut runstate,
+            mut effects,
+            cursed,
+            mut unknown_enchantment,
         ) = data;
 
         for (entity, useitem) in (&entities, &wants_use).join() {
-            let mut used_item = true;
-
-            //targeting
-            let mut targets: Vec<Entity> = Vec::new();
-            match useitem.target {
-                None => targets.push(*player_entity),
-                Some(target) => {
-                    let area_of_effect = aoe.get(useitem.item);
-                    match area_of_effect {
-                        None => {
-                            //Single target
-                            let idx = map.xy_idx(target.x, target.y);
-                            for mob in map.tile_content[idx].iter() {
-                                targets.push(*mob);
-                            }
-                        }
-                        Some(area_of_effect) => {
-                            // AoE
-                            let mut affected_tiles =
-                                field_of_view(target, area_of_effect.radius, &*map);
-                            affected_tiles.retain(|p| p.x < map.width - 1 && p.y < map.height - 1);
-                            for tile_idx in affected_tiles.iter() {
-                                let idx = map.xy_idx(tile_idx.x, tile_idx.y);
-                                for mob in map.tile_content[idx].iter() {
-                                    targets.push(*mob);
-                                }
-                                particle_builder.request(
-                                    tile_idx.x,
-                                    tile_idx.y,
-                                    RGB::named(ORANGE),
-                                    RGB::named(BLACK),
-                                    to_cp437('░'),
-                                    200.0,
-                                )
-                            }
-                        }
-                    }
-                }
-            }
-
-            let item_edible = edible.get(useitem.item);
-            match item_edible {
-                None => {}
-                Some(edible) => {
-                    used_item = true;
-                    let target = targets[0];
-                    let hc = hunger_clocks.get_mut(target);
-                    if let Some(hc) = hc {
-                        hc.state = HungerState::WellFed;
-                        hc.duration = 20;
-                        gamelog.entries.push(format!(
-                            "You eat the {}",
-                            names.get(useitem.item).unwrap().name
-                        ));
-                    }
-                }
-            }
-
-            let is_mapper = magic_mapper.get(useitem.item);
-            match is_mapper {
-                None => {}
-                Some(_) => {
-                    used_item = true;
-                    *runstate = RunState::MagicMapReveal { row: 0 };
-                    gamelog
-                        .entries
-                        .push("The map is revealed to you!".to_string());
-                }
-            }
+            // equipment goes on the user, everything else is an effect
+            if let Some(can_equip) = equippable.get(useitem.item) {
+                let worn: Vec<(Entity, EquipmentSlot)> = (&entities, &equipped)
+                    .join()
+                    .filter(|(_item, worn)| worn.owner == entity)
+                    .map(|(item, worn)| (item, worn.slot))
+                    .collect();
+                let target_slot = equip_slot(can_equip.slot, &worn);
 
-            let item_equippable = equippable.get(useitem.item);
-            match item_equippable {
-                None => {}
-                Some(can_equip) => {
-                    let target_slot = can_equip.slot;
-                    let target = targets[0];
+                // whatever's in the way goes back in the pack: the old item in the slot,
+                // and the shield or two-handed weapon that can't be held alongside the new one
+                let to_unequip: Vec<Entity> = worn
+                    .iter()
+                    .filter(|(item_entity, slot)| {
+                        let blocked = match (target_slot, *slot) {
+                            (EquipmentSlot::Melee, EquipmentSlot::Shield) => can_equip.two_handed,
+                            (EquipmentSlot::Shield, EquipmentSlot::Melee) => equippable
+                                .get(*item_entity)
+                                .map_or(false, |weapon| weapon.two_handed),
+                            _ => false,
+                        };
+                        *slot == target_slot || blocked
+                    })
+                    .map(|(item_entity, _slot)| *item_entity)
+                    .collect();
 
-                    let mut to_unequip: Vec<Entity> = Vec::new();
-                    for (item_entity, already_equipped, name) in
-                        (&entities, &equipped, &names).join()
-                    {
-                        if already_equipped.owner == target && already_equipped.slot == target_slot
-                        {
-                            to_unequip.push(item_entity);
-                            if target == *player_entity {
-                                gamelog
-                                    .entries
-                                    .push(format!("You unequip the {}.", name.name));
-                            }
+                // a cursed item won't come off to make room
+                if let Some(stuck) = to_unequip.iter().find(|item| cursed.get(**item).is_some()) {
+                    if entity == *player_entity {
+                        if let Some(name) = names.get(*stuck) {
+                            gamelog.add(
+                                LogCategory::Item,
+                                format!("You can't remove the {}, it's cursed!", name.name),
+                            );
                         }
                     }
-
-                    for item in to_unequip.iter() {
-                        equipped.remove(*item);
-                        backpack
-                            .insert(*item, InBackpack { owner: target })
-                            .expect("Unable to insert into backpack");
-                    }
-
-                    equipped
-                        .insert(
-                            useitem.item,
-                            Equipped {
-                                owner: target,
-                                slot: target_slot,
-                            },
-                        )
-                        .expect("Unable to equip item.");
-                    backpack.remove(useitem.item);
-                    if target == *player_entity {
-                        gamelog.entries.push(format!(
-                            "You equip the {}.",
-                            names.get(useitem.item).unwrap().name
-                        ))
-                    }
+                    continue;
                 }
-            }
 
-            // if item heals, apply healing
-            let item_heals = healing.get(useitem.item);
-            match item_heals {
-                None => {}
-                Some(healer) => {
-                    used_item = false;
-                    for target in targets.iter() {
-                        let stats = combat_stats.get_mut(*target);
-                        if let Some(stats) = stats {
-                            stats.hp = i32::min(stats.max_hp, stats.hp + healer.restore_hp);
-                            if entity == *player_entity {
-                                gamelog.entries.push(format!(
-                                    "You used the {}, healing {} hit points.",
-                                    names.get(useitem.item).unwrap().name,
-                                    healer.restore_hp
-                                ));
-                            }
-                            used_item = true;
-
-                            let pos = positions.get(*target);
-                            if let Some(pos) = pos {
-                                particle_builder.request(
-                                    pos.x,
-                                    pos.y,
-                                    RGB::named(YELLOW),
-                                    RGB::named(BLACK),
-                                    to_cp437('♥'),
-                                    200.0,
-                                );
-                            }
+                for item in to_unequip.iter() {
+                    if entity == *player_entity {
+                        if let Some(name) = names.get(*item) {
+                            gamelog
+                                .add(LogCategory::Item, format!("You unequip the {}.", name.name));
                         }
                     }
+                    equipped.remove(*item);
+                    backpack
+                        .insert(*item, InBackpack { owner: entity })
+                        .expect("Unable to insert into backpack");
                 }
-            }
-            let mut add_confusion = Vec::new();
-            {
-                let item_confuses = confuse.get(useitem.item);
-                match item_confuses {
-                    None => {}
-                    Some(confusion) => {
-                        used_item = false;
-                        for mob in targets.iter() {
-                            add_confusion.push((*mob, confusion.turns));
-                            if entity == *player_entity {
-                                let mob_name = &names.get(*mob).unwrap().name;
-                                let item_name = &names.get(useitem.item).unwrap().name;
-                                gamelog.entries.push(format!(
-                                    "You use {} on {}, confusing them.",
-                                    item_name, mob_name
-                                ));
-                                let pos = positions.get(*mob);
-                                if let Some(pos) = pos {
-                                    particle_builder.request(
-                                        pos.x,
-                                        pos.y,
-                                        RGB::named(RED),
-                                        RGB::named(BLACK),
-                                        to_cp437('?'),
-                                        200.0,
-                                    );
-                                }
-                            }
-                            used_item = true;
-                        }
-                    }
-                }
-            }
-            for mob in add_confusion.iter() {
-                confuse
-                    .insert(mob.0, Confusion { turns: mob.1 })
-                    .expect("Unable to insert status");
-            }
 
-            //if damaging, apply damage to target.
-            let item_damages = inflict_damage.get(useitem.item);
-            match item_damages {
-                None => {}
-                Some(damage) => {
-                    used_item = false;
-                    for mob in targets.iter() {
-                        SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage);
-                        if entity == *player_entity {
-                            let mob_name = names.get(*mob).unwrap();
-                            let item_name = names.get(useitem.item).unwrap();
-                            gamelog.entries.push(format!(
-                                "You use the {} on {}, inflicting {} damage.",
-                                item_name.name, mob_name.name, damage.damage
-                            ));
-
-                            let pos = positions.get(*mob);
-                            if let Some(pos) = pos {
-                                particle_builder.request(
-                                    pos.x,
-                                    pos.y,
-                                    RGB::named(RED),
-                                    RGB::named(BLACK),
-                                    to_cp437('‼'),
-                                    200.0,
-                                );
-                            }
-                        }
-                        used_item = true;
-                    }
-                }
-            }
-            //if consumable, consume item
-            if used_item {
-                let consumable = consumables.get(useitem.item);
-                match consumable {
-                    None => {}
-                    Some(_) => {
-                        entities.delete(useitem.item).expect("Delete failed");
+                equipped
+                    .insert(
+                        useitem.item,
+                        Equipped {
+                            owner: entity,
+                            slot: target_slot,
+                        },
+                    )
+                    .expect("Unable to equip item.");
+                backpack.remove(useitem.item);
+                if entity == *player_entity {
+                    gamelog.add(
+                        LogCategory::Item,
+                        format!("You equip the {}.", names.get(useitem.item).unwrap().name),
+                    );
+                    // wearing it is how you find out what it's really like
+                    if unknown_enchantment.remove(useitem.item).is_some()
+                        && cursed.get(useitem.item).is_some()
+                    {
+                        gamelog.add(
+                            LogCategory::Item,
+                            "It's cursed! You can't take it off.".to_string(),
+                        );
                     }
                 }
+                continue;
             }
+
+            //targeting, no target means the user
+            let targets = match useitem.target {
+                None => Targets::Single { target: entity },
+                Some(target) => match aoe.get(useitem.item) {
+                    None => Targets::Tile {
+                        tile_idx: map.xy_idx(target.x, target.y),
+                    },
+                    Some(area_of_effect) => Targets::Area {
+                        center: target,
+                        radius: area_of_effect.radius,
+                    },
+                },
+            };
+            effects.add_effect(
+                Some(entity),
+                EffectType::ItemUse { item: useitem.item },
+                targets,
+            );
         }
         wants_use.clear();
     }
 }
 
+/// Where an item goes given what's already worn. A ring takes whichever ring finger
+/// is free, or replaces the first one when both are taken.
+fn equip_slot(slot: EquipmentSlot, worn: &[(Entity, EquipmentSlot)]) -> EquipmentSlot {
+    let taken = |slot: EquipmentSlot| worn.iter().any(|(_item, worn_slot)| *worn_slot == slot);
+    match slot {
+        EquipmentSlot::Ring | EquipmentSlot::SecondRing => {
+            if taken(EquipmentSlot::Ring) && !taken(EquipmentSlot::SecondRing) {
+                EquipmentSlot::SecondRing
+            } else {
+                EquipmentSlot::Ring
+            }
+        }
+        _ => slot,
+    }
+}
+
 pub struct ItemDropSystem {}
 impl<'a> System<'a> for ItemDropSystem {
     type SystemData = (
@@ -343,6 +254,7 @@ impl<'a> System<'a> for ItemDropSystem {
         ReadStorage<'a, Name>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, InBackpack>,
+        ReadExpect<'a, MagicItemNames>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -354,6 +266,7 @@ impl<'a> System<'a> for ItemDropSystem {
             names,
             mut positions,
             mut backpack,
+            magic_items,
         ) = data;
 
         for (entity, to_drop) in (&entities, &wants_drop).join() {
@@ -375,46 +288,260 @@ impl<'a> System<'a> for ItemDropSystem {
             backpack.remove(to_drop.item);
 
             if entity == *player_entity {
-                gamelog.entries.push(format!(
-                    "You drop the {}",
-                    names.get(to_drop.item).unwrap().name
-                ));
+                gamelog.add(
+                    LogCategory::Item,
+                    format!(
+                        "You drop the {}",
+                        magic_items.display(&names.get(to_drop.item).unwrap().name)
+                    ),
+                );
             }
         }
         wants_drop.clear();
     }
 }
 
-pub fn get_item(world: &mut World) {
+pub struct ItemRemoveSystem {}
+impl<'a> System<'a> for ItemRemoveSystem {
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToRemoveItem>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, Equipped>,
+        WriteStorage<'a, InBackpack>,
+        ReadStorage<'a, Cursed>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut gamelog,
+            entities,
+            mut wants_remove,
+            names,
+            mut equipped,
+            mut backpack,
+            cursed,
+        ) = data;
+
+        for (entity, to_remove) in (&entities, &wants_remove).join() {
+            // only what the remover is actually wearing can come off
+            if equipped
+                .get(to_remove.item)
+                .map_or(true, |worn| worn.owner != entity)
+            {
+                continue;
+            }
+            if cursed.get(to_remove.item).is_some() {
+                if entity == *player_entity {
+                    if let Some(name) = names.get(to_remove.item) {
+                        gamelog.add(
+                            LogCategory::Item,
+                            format!("You can't remove the {}, it's cursed!", name.name),
+                        );
+                    }
+                }
+                continue;
+            }
+            equipped.remove(to_remove.item);
+            backpack
+                .insert(to_remove.item, InBackpack { owner: entity })
+                .expect("Unable to insert into backpack");
+
+            if entity == *player_entity {
+                if let Some(name) = names.get(to_remove.item) {
+                    gamelog.add(LogCategory::Item, format!("You unequip the {}.", name.name));
+                }
+            }
+        }
+        wants_remove.clear();
+    }
+}
+
+/// How much a load is holding its carrier back
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Burden {
+    Unburdened,
+    /// moves at half speed
+    Burdened,
+    /// can't move at all until something is dropped
+    Overloaded,
+}
+
+/// what a creature can carry before it slows down; the stronger it is the more it can lift
+pub fn carry_capacity(stats: &CombatStats) -> f32 {
+    20.0 + stats.power as f32 * 4.0
+}
+
+/// the total weight of everything the owner carries or wears; a stack weighs each of its items
+pub fn carried_weight(
+    owner: Entity,
+    backpack: &ReadStorage<InBackpack>,
+    equipped: &ReadStorage<Equipped>,
+    weights: &ReadStorage<Weight>,
+    stackables: &ReadStorage<Stackable>,
+) -> f32 {
+    let packed: f32 = (backpack, weights, stackables.maybe())
+        .join()
+        .filter(|(pack, _weight, _stack)| pack.owner == owner)
+        .map(|(_pack, weight, stack)| weight.weight * stack.map_or(1, |s| s.quantity) as f32)
+        .sum();
+    let worn: f32 = (equipped, weights)
+        .join()
+        .filter(|(worn, _weight)| worn.owner == owner)
+        .map(|(_worn, weight)| weight.weight)
+        .sum();
+    packed + worn
+}
+
+/// anything over capacity is a burden, and half as much again is too much to move with
+pub fn burden(carried: f32, capacity: f32) -> Burden {
+    if carried > capacity * 1.5 {
+        Burden::Overloaded
+    } else if carried > capacity {
+        Burden::Burdened
+    } else {
+        Burden::Unburdened
+    }
+}
+
+/// the player's carried weight, capacity and burden
+pub fn player_burden(ecs: &World) -> (f32, f32, Burden) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let carried = carried_weight(
+        player_entity,
+        &ecs.read_storage::<InBackpack>(),
+        &ecs.read_storage::<Equipped>(),
+        &ecs.read_storage::<Weight>(),
+        &ecs.read_storage::<Stackable>(),
+    );
+    let capacity = ecs
+        .read_storage::<CombatStats>()
+        .get(player_entity)
+        .map_or(0.0, carry_capacity);
+    (carried, capacity, burden(carried, capacity))
+}
+
+/// copies each listed component the source has onto the target
+macro_rules! copy_components {
+    ($ecs:expr, $from:expr, $to:expr, $( $type:ty ),*) => {
+        $(
+            {
+                let component = $ecs.read_storage::<$type>().get($from).cloned();
+                if let Some(component) = component {
+                    $ecs.write_storage::<$type>()
+                        .insert($to, component)
+                        .expect("Unable to insert component");
+                }
+            }
+        )*
+    };
+}
+
+/// Takes one item off a carried stack, as an entity of its own in the same pack, so it can be
+/// used, dropped or thrown while the rest stay behind. Anything else comes back as it is.
+pub fn split_one(ecs: &mut World, item: Entity) -> Entity {
+    let quantity = ecs
+        .read_storage::<Stackable>()
+        .get(item)
+        .map_or(1, |stack| stack.quantity);
+    if quantity < 2 {
+        return item;
+    }
+    let owner = match ecs.read_storage::<InBackpack>().get(item) {
+        Some(pack) => pack.owner,
+        None => return item,
+    };
+
+    let single = ecs
+        .create_entity()
+        .with(InBackpack { owner })
+        .with(Stackable { quantity: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+    // a copy of the stack's own components, so enchantments and the like come along too
+    copy_components!(
+        ecs,
+        item,
+        single,
+        Item,
+        Name,
+        Renderable,
+        Consumable,
+        Ranged,
+        InflictsDamage,
+        AreaOfEffect,
+        Confusion,
+        ProvidesHealing,
+        Equippable,
+        MeleePowerBonus,
+        DefenseBonus,
+        ProvidesFood,
+        MagicMapper,
+        Teleports,
+        Hidden,
+        EntryTrigger,
+        SingleActivation,
+        Launcher,
+        Ammunition,
+        Thrown,
+        InflictsStatus,
+        Enchantment,
+        Cursed,
+        UnknownEnchantment,
+        Identifies,
+        Weight
+    );
+    if let Some(stack) = ecs.write_storage::<Stackable>().get_mut(item) {
+        stack.quantity -= 1;
+    }
+    single
+}
+
+/// the item lying where the player stands, if there is one
+fn item_under_player(world: &World) -> Option<Entity> {
     let player_pos = world.fetch::<Point>();
-    let player_entity = world.fetch::<Entity>();
     let entities = world.entities();
     let items = world.read_storage::<Item>();
     let positions = world.read_storage::<Position>();
-    let mut gamelog = world.fetch_mut::<GameLog>();
+    let found = (&entities, &items, &positions)
+        .join()
+        .find(|(_item_entity, _item, position)| {
+            position.x == player_pos.x && position.y == player_pos.y
+        })
+        .map(|(item_entity, _item, _position)| item_entity);
+    found
+}
 
-    let mut target: Option<Entity> = None;
-    for (item_entity, _item, position) in (&entities, &items, &positions).join() {
-        if position.x == player_pos.x && position.y == player_pos.y {
-            target = Some(item_entity);
-        }
+pub fn get_item(world: &mut World) {
+    match item_under_player(world) {
+        None => world.fetch_mut::<GameLog>().add(
+            LogCategory::Item,
+            "There is nothing here to pick up".to_string(),
+        ),
+        Some(item) => pick_up(world, item),
+    }
+}
 
-        match target {
-            None => gamelog
-                .entries
-                .push("There is nothing here to pick up".to_string()),
-            Some(item) => {
-                let mut pickup = world.write_storage::<WantsToPickupItem>();
-                pickup
-                    .insert(
-                        *player_entity,
-                        WantsToPickupItem {
-                            acquired_by: *player_entity,
-                            item,
-                        },
-                    )
-                    .expect("Unable to insert item");
-            }
-        }
+/// with auto-pickup on, whatever the player steps onto goes straight into the pack
+pub fn auto_pickup(world: &mut World) {
+    if let Some(item) = item_under_player(world) {
+        pick_up(world, item);
     }
 }
+
+fn pick_up(world: &mut World, item: Entity) {
+    let player_entity = *world.fetch::<Entity>();
+    let mut pickup = world.write_storage::<WantsToPickupItem>();
+    pickup
+        .insert(
+            player_entity,
+            WantsToPickupItem {
+                acquired_by: player_entity,
+                item,
+            },
+        )
+        .expect("Unable to insert item");
+}
@@ -1,8 +1,9 @@
 use crate::{
-    components::{CombatStats, HungerClock, Name, SufferDamage, WantsToMelee},
-    gamelog::GameLog,
-    particle_system::ParticleBuilder,
-    DefenseBonus, Equipped, HungerState, MeleePowerBonus, Position,
+    components::{CombatStats, HungerClock, Name, WantsToMelee},
+    effects::{EffectQueue, EffectType, Targets},
+    gamelog::{GameLog, LogCategory},
+    settings::{LogVerbosity, Settings},
+    DefenseBonus, Equipped, HungerState, MeleePowerBonus,
 };
 use bracket_lib::prelude::*;
 use specs::prelude::*;
@@ -16,13 +17,12 @@ impl<'a> System<'a> for MeleeCombatSystem {
         WriteStorage<'a, WantsToMelee>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, CombatStats>,
-        WriteStorage<'a, SufferDamage>,
+        WriteExpect<'a, EffectQueue>,
         ReadStorage<'a, MeleePowerBonus>,
         ReadStorage<'a, DefenseBonus>,
         ReadStorage<'a, Equipped>,
-        WriteExpect<'a, ParticleBuilder>,
-        ReadStorage<'a, Position>,
         ReadStorage<'a, HungerClock>,
+        ReadExpect<'a, Settings>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -32,32 +32,33 @@ impl<'a> System<'a> for MeleeCombatSystem {
             mut wants_melee,
             names,
             combat_stats,
-            mut inflict_damage,
+            mut effects,
             power_bonus,
             defense_bonus,
             equipped,
-            mut particle_builder,
-            positions,
             hunger_clocks,
+            settings,
         ) = data;
 
         // grabs all entities that can melee, are named, and have stats
         for (entity, wants_melee, name, stats) in
             (&entities, &wants_melee, &names, &combat_stats).join()
         {
-            let pos = positions.get(wants_melee.target);
-            if let Some(pos) = pos {
-                particle_builder.request(
-                    pos.x,
-                    pos.y,
-                    RGB::named(ORANGE),
-                    RGB::named(BLACK),
-                    to_cp437('‼'),
-                    200.0,
-                );
-            }
+            effects.add_effect(
+                Some(entity),
+                EffectType::Particle {
+                    glyph: to_cp437('‼'),
+                    fg: RGB::named(ORANGE),
+                    bg: RGB::named(BLACK),
+                    lifetime: 200.0,
+                },
+                Targets::Single {
+                    target: wants_melee.target,
+                },
+            );
             // if entity hp is > 0, grab target stats
             if stats.hp > 0 {
+                // every worn item with a power bonus counts, whatever slot it's in
                 let mut offensive_bonus = 0;
                 for (_item_entity, power_bonus, equipped_by) in
                     (&entities, &power_bonus, &equipped).join()
@@ -65,11 +66,11 @@ impl<'a> System<'a> for MeleeCombatSystem {
                     if equipped_by.owner == entity {
                         offensive_bonus += power_bonus.power;
                     }
-                    let hc = hunger_clocks.get(entity);
-                    if let Some(hc) = hc {
-                        if hc.state == HungerState::WellFed {
-                            offensive_bonus += 1;
-                        }
+                }
+                let hc = hunger_clocks.get(entity);
+                if let Some(hc) = hc {
+                    if hc.state == HungerState::WellFed {
+                        offensive_bonus += 1;
                     }
                 }
 
@@ -86,22 +87,33 @@ impl<'a> System<'a> for MeleeCombatSystem {
                             defensive_bonus += defense_bonus.defense;
                         }
                     }
-                    let damage = i32::max(
-                        0,
-                        stats.power + offensive_bonus - target_stats.defense - defensive_bonus,
-                    );
+                    let power = stats.power + offensive_bonus;
+                    let defense = target_stats.defense + defensive_bonus;
+                    let damage = i32::max(0, power - defense);
 
                     if damage == 0 {
-                        log.entries.push(format!(
-                            "{} is unable to hurt {}",
-                            &name.name, &target_name.name
-                        ));
+                        if settings.log_verbosity != LogVerbosity::Brief {
+                            log.add(
+                                LogCategory::Combat,
+                                format!("{} is unable to hurt {}", &name.name, &target_name.name),
+                            );
+                        }
                     } else {
-                        log.entries.push(format!(
+                        let mut message = format!(
                             "{} hits {} for {} damage.",
                             &name.name, &target_name.name, &damage
-                        ));
-                        SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage);
+                        );
+                        if settings.log_verbosity == LogVerbosity::Verbose {
+                            message += &format!(" ({} power vs {} defense)", power, defense);
+                        }
+                        log.add(LogCategory::Combat, message);
+                        effects.add_effect(
+                            Some(entity),
+                            EffectType::Damage { amount: damage },
+                            Targets::Single {
+                                target: wants_melee.target,
+                            },
+                        );
                     }
                 }
             }
@@ -1,14 +1,18 @@
 // mod.rs for ECS
 pub mod damage;
 pub mod hunger;
+pub mod initiative;
 pub mod inventory;
 pub mod map_indexing;
 pub mod melee_combat;
 pub mod monster_ai;
 pub mod particle_system;
 pub mod player;
+pub mod ranged_combat;
 pub mod saveload;
 pub mod spawn_table;
 pub mod spawner;
+pub mod status;
+pub mod travel;
 pub mod trigger;
 pub mod visibility;
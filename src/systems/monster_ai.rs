@@ -1,5 +1,5 @@
 use crate::components::*;
-use crate::{Map, RunState};
+use crate::{flow_field::FlowField, Map};
 use bracket_lib::prelude::*;
 use specs::prelude::*;
 
@@ -11,15 +11,28 @@ impl<'a> System<'a> for MonsterAI {
         WriteExpect<'a, Map>,
         ReadExpect<'a, Point>,
         ReadExpect<'a, Entity>,
-        ReadExpect<'a, RunState>,
         Entities<'a>,
+        ReadStorage<'a, MyTurn>,
         WriteStorage<'a, Viewshed>,
         ReadStorage<'a, Monster>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, WantsToMelee>,
-        WriteStorage<'a, Confusion>,
+        ReadStorage<'a, StatusEffects>,
+        ReadStorage<'a, Confusion>,
+        ReadStorage<'a, InflictsStatus>,
         WriteExpect<'a, ParticleBuilder>,
         WriteStorage<'a, EntityMoved>,
+        WriteStorage<'a, AiState>,
+        ReadStorage<'a, CombatStats>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, FlowField>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Item>,
+        ReadStorage<'a, Ranged>,
+        ReadStorage<'a, InflictsDamage>,
+        ReadStorage<'a, ProvidesHealing>,
+        WriteStorage<'a, WantsToUseItem>,
+        WriteStorage<'a, WantsToPickupItem>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -27,87 +40,284 @@ impl<'a> System<'a> for MonsterAI {
             mut map,
             player_pos,
             player_entity,
-            runstate,
             entities,
+            turns,
             mut viewshed,
             monster,
             mut position,
             mut wants_to_melee,
-            mut confused,
+            statuses,
+            confusion_items,
+            status_items,
             mut particle_builder,
             mut entity_moved,
+            mut ai_states,
+            combat_stats,
+            mut rng,
+            mut flow_field,
+            backpack,
+            items,
+            ranged,
+            inflicts_damage,
+            healing,
+            mut wants_use,
+            mut wants_pickup,
         ) = data;
 
-        // break out of monster_ai if it is not the monster's turn
-        if *runstate != RunState::MonsterTurn {
+        // break out of monster_ai if no monster has a turn this tick
+        if (&monster, &turns).join().next().is_none() {
             return;
         }
 
-        for (entity, mut viewshed, _monster, mut position) in
-            (&entities, &mut viewshed, &monster, &mut position).join()
-        {
-            let mut can_act = true;
+        let player_status = statuses.get(*player_entity);
+
+        // one pair of distance maps serves every monster chasing or fleeing this turn
+        flow_field.update(&map, *player_pos);
 
-            let is_confused = confused.get_mut(entity);
-            if let Some(is_confused) = is_confused {
-                is_confused.turns -= 1;
-                if is_confused.turns < 1 {
-                    confused.remove(entity);
+        // monsters loaded from saves made before AI states existed get the defaults
+        let missing_ai: Vec<Entity> = (&entities, &monster, !&ai_states)
+            .join()
+            .map(|(entity, _monster, _no_ai)| entity)
+            .collect();
+        for entity in missing_ai {
+            ai_states
+                .insert(entity, AiState::default())
+                .expect("Unable to insert AI state");
+        }
+
+        for (entity, mut viewshed, _monster, mut position, ai, _turn) in (
+            &entities,
+            &mut viewshed,
+            &monster,
+            &mut position,
+            &mut ai_states,
+            &turns,
+        )
+            .join()
+        {
+            if let Some(status) = statuses.get(entity) {
+                if status.has(StatusKind::Confused) {
+                    particle_builder.request(
+                        position.x,
+                        position.y,
+                        RGB::from(MAGENTA),
+                        RGB::from(BLACK),
+                        to_cp437('?'),
+                        200.0,
+                    );
+                    continue;
+                }
+                if status.loses_turn() {
+                    continue;
                 }
-                can_act = false;
-
-                particle_builder.request(
-                    position.x,
-                    position.y,
-                    RGB::from(MAGENTA),
-                    RGB::from(BLACK),
-                    to_cp437('?'),
-                    200.0,
-                )
             }
 
-            if can_act {
-                // grabs distance from monster to player via pythagorean distance algorithm
-                // TODO: evaluate potential for differnet algos
-                let distance = DistanceAlg::Pythagoras
-                    .distance2d(Point::new(position.x, position.y), *player_pos);
+            if ai.uses_items {
+                // drink a potion before things get desperate
+                let hurt = combat_stats
+                    .get(entity)
+                    .map_or(false, |stats| stats.hp < stats.max_hp / 2);
+                let potion = (&entities, &backpack, &healing)
+                    .join()
+                    .find(|(_item, pack, _heals)| pack.owner == entity)
+                    .map(|(item, _pack, _heals)| item);
+                if let (true, Some(potion)) = (hurt, potion) {
+                    wants_use
+                        .insert(
+                            entity,
+                            WantsToUseItem {
+                                item: potion,
+                                target: None,
+                            },
+                        )
+                        .expect("Unable to insert intent");
+                    continue;
+                }
 
-                // in melee range? melee
-                if distance < 1.5 {
-                    wants_to_melee
+                // and grab any lying underfoot for later
+                let idx = map.xy_idx(position.x, position.y);
+                let underfoot = map.tile_content[idx]
+                    .iter()
+                    .find(|e| items.get(**e).is_some() && healing.get(**e).is_some())
+                    .copied();
+                if let Some(potion) = underfoot {
+                    wants_pickup
                         .insert(
                             entity,
-                            WantsToMelee {
-                                target: *player_entity,
+                            WantsToPickupItem {
+                                acquired_by: entity,
+                                item: potion,
                             },
                         )
-                        .expect("Unable to insert attack");
+                        .expect("Unable to insert intent");
+                    continue;
                 }
-                // if out of melee range but visible, move towards player
-                else if viewshed.visible_tiles.contains(&*player_pos) {
-                    // Path to player
-                    // TODO: research a_star_search, see if pathing algo is ideal
-                    let path = a_star_search(
-                        map.xy_idx(position.x, position.y),
-                        map.xy_idx(player_pos.x, player_pos.y),
-                        &mut *map,
-                    );
-                    // if the pathing algo is successful and the monster isn't in front of the player
-                    // unblock old spot, step forward, block new spot, trigger viewshed redraw
-                    if path.success && path.steps.len() > 1 {
-                        let mut idx = map.xy_idx(position.x, position.y);
-                        map.blocked[idx] = false;
-                        position.x = path.steps[1] as i32 % map.width;
-                        position.y = path.steps[1] as i32 / map.width;
-                        entity_moved
-                            .insert(entity, EntityMoved {})
-                            .expect("Unable to insert marker");
-                        idx = map.xy_idx(position.x, position.y);
-                        map.blocked[idx] = true;
-                        viewshed.dirty = true;
+            }
+
+            // grabs distance from monster to player via pythagorean distance algorithm
+            let distance =
+                DistanceAlg::Pythagoras.distance2d(Point::new(position.x, position.y), *player_pos);
+            let sees_player = viewshed.visible_tiles.contains(&*player_pos);
+            let wounded = combat_stats.get(entity).map_or(false, |stats| {
+                (stats.hp as f32) < stats.max_hp as f32 * ai.flee_below
+            });
+
+            ai.mode = next_mode(ai, sees_player, wounded, *player_pos);
+
+            let moved = match ai.mode {
+                AiMode::Idle => false,
+                AiMode::Wander => {
+                    let idx = map.xy_idx(position.x, position.y);
+                    let exits = map.get_available_exits(idx);
+                    if exits.is_empty() {
+                        false
+                    } else {
+                        let roll = rng.roll_dice(1, exits.len() as i32) as usize - 1;
+                        move_to(&mut map, &mut position, exits[roll].0);
+                        true
                     }
                 }
+                AiMode::Chase { .. } => {
+                    let player_confused =
+                        player_status.map_or(false, |status| status.has(StatusKind::Confused));
+                    let ability = (&entities, &backpack, &ranged)
+                        .join()
+                        .filter(|(_item, pack, range)| {
+                            pack.owner == entity && distance <= range.range as f32
+                        })
+                        .map(|(item, _pack, _range)| item)
+                        .find(|item| {
+                            // no point putting a status on the player that they already have
+                            let status_wasted = match status_items.get(*item) {
+                                Some(inflicts) => {
+                                    player_status.map_or(false, |status| status.has(inflicts.kind))
+                                }
+                                None => player_confused,
+                            };
+                            inflicts_damage.get(*item).is_some()
+                                || ((confusion_items.get(*item).is_some()
+                                    || status_items.get(*item).is_some())
+                                    && !status_wasted)
+                        });
+                    // chasers in sight of the player shoot or cast if they can, and close in to melee otherwise
+                    if let Some(ability) = ability {
+                        wants_use
+                            .insert(
+                                entity,
+                                WantsToUseItem {
+                                    item: ability,
+                                    target: Some(*player_pos),
+                                },
+                            )
+                            .expect("Unable to insert intent");
+                        false
+                    } else if distance < 1.5 {
+                        wants_to_melee
+                            .insert(
+                                entity,
+                                WantsToMelee {
+                                    target: *player_entity,
+                                },
+                            )
+                            .expect("Unable to insert attack");
+                        false
+                    } else {
+                        let idx = map.xy_idx(position.x, position.y);
+                        match flow_field.step_toward(idx, &map.get_available_exits(idx)) {
+                            Some(exit) => {
+                                move_to(&mut map, &mut position, exit);
+                                true
+                            }
+                            None => false,
+                        }
+                    }
+                }
+                AiMode::Search { x, y, .. } => {
+                    let dest = map.xy_idx(x, y);
+                    step_towards(&mut map, &mut position, dest)
+                }
+                AiMode::Flee => {
+                    let idx = map.xy_idx(position.x, position.y);
+                    let escape = flow_field.step_away(idx, &map.get_available_exits(idx));
+                    match escape {
+                        Some(exit) => {
+                            move_to(&mut map, &mut position, exit);
+                            true
+                        }
+                        None => {
+                            // cornered, so fight
+                            if distance < 1.5 {
+                                wants_to_melee
+                                    .insert(
+                                        entity,
+                                        WantsToMelee {
+                                            target: *player_entity,
+                                        },
+                                    )
+                                    .expect("Unable to insert attack");
+                            }
+                            false
+                        }
+                    }
+                }
+            };
+
+            if moved {
+                entity_moved
+                    .insert(entity, EntityMoved {})
+                    .expect("Unable to insert marker");
+                viewshed.dirty = true;
             }
         }
     }
 }
+
+/// Picks this turn's mode. Seeing the player means chasing (or fleeing when hurt);
+/// losing sight of them means searching where they were last seen until patience runs out.
+fn next_mode(ai: &AiState, sees_player: bool, wounded: bool, player_pos: Point) -> AiMode {
+    if sees_player {
+        if wounded {
+            return AiMode::Flee;
+        }
+        return AiMode::Chase {
+            x: player_pos.x,
+            y: player_pos.y,
+        };
+    }
+
+    match ai.mode {
+        AiMode::Chase { x, y } if ai.search_turns > 0 => AiMode::Search {
+            x,
+            y,
+            turns_left: ai.search_turns,
+        },
+        AiMode::Search { x, y, turns_left } if turns_left > 1 => AiMode::Search {
+            x,
+            y,
+            turns_left: turns_left - 1,
+        },
+        AiMode::Idle | AiMode::Wander => ai.mode,
+        _ => ai.resting_mode(),
+    }
+}
+
+/// Takes one step along the path to dest. Returns true if the monster moved.
+/// Only searching monsters use this, as each one is headed somewhere different.
+fn step_towards(map: &mut Map, position: &mut Position, dest: usize) -> bool {
+    let path = a_star_search(map.xy_idx(position.x, position.y), dest, &*map);
+    // if the pathing algo is successful and the monster isn't already there, step forward
+    if path.success && path.steps.len() > 1 {
+        move_to(map, position, path.steps[1]);
+        return true;
+    }
+    false
+}
+
+/// unblock old spot, step to the new one, block new spot
+fn move_to(map: &mut Map, position: &mut Position, idx: usize) {
+    let old_idx = map.xy_idx(position.x, position.y);
+    map.blocked[old_idx] = false;
+    position.x = idx as i32 % map.width;
+    position.y = idx as i32 / map.width;
+    map.blocked[idx] = true;
+}
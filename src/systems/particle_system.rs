@@ -1,5 +1,6 @@
-use crate::components::{ParticleLifetime, Position, Renderable};
-use bracket_lib::terminal::{BTerm, FontCharType, RGB};
+use crate::components::{Hidden, ParticleLifetime, Position, Renderable};
+use crate::settings::Settings;
+use bracket_lib::terminal::{FontCharType, RGB};
 use specs::prelude::*;
 
 struct ParticleRequest {
@@ -9,6 +10,7 @@ struct ParticleRequest {
     bg: RGB,
     glyph: FontCharType,
     lifetime: f32,
+    delay: f32,
 }
 
 pub struct ParticleBuilder {
@@ -30,6 +32,21 @@ impl ParticleBuilder {
         bg: RGB,
         glyph: FontCharType,
         lifetime: f32,
+    ) {
+        self.request_delayed(x, y, fg, bg, glyph, lifetime, 0.0);
+    }
+
+    /// like request, but the particle only shows up once delay ms have passed
+    #[allow(clippy::too_many_arguments)]
+    pub fn request_delayed(
+        &mut self,
+        x: i32,
+        y: i32,
+        fg: RGB,
+        bg: RGB,
+        glyph: FontCharType,
+        lifetime: f32,
+        delay: f32,
     ) {
         self.requests.push(ParticleRequest {
             x,
@@ -38,6 +55,7 @@ impl ParticleBuilder {
             bg,
             glyph,
             lifetime,
+            delay,
         });
     }
 }
@@ -51,10 +69,26 @@ impl<'a> System<'a> for ParticleSpawnSystem {
         WriteStorage<'a, Renderable>,
         WriteStorage<'a, ParticleLifetime>,
         WriteExpect<'a, ParticleBuilder>,
+        WriteStorage<'a, Hidden>,
+        ReadExpect<'a, Settings>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut positions, mut renderables, mut particles, mut particle_builder) = data;
+        let (
+            entities,
+            mut positions,
+            mut renderables,
+            mut particles,
+            mut particle_builder,
+            mut hidden,
+            settings,
+        ) = data;
+        // the settings stretch or shrink every particle, delays included so effects stay in step
+        let scale = settings.particle_duration;
+        if scale <= 0.0 {
+            particle_builder.requests.clear();
+            return;
+        }
         for new_particle in particle_builder.requests.iter() {
             let p = entities.create();
             positions
@@ -81,24 +115,40 @@ impl<'a> System<'a> for ParticleSpawnSystem {
                 .insert(
                     p,
                     ParticleLifetime {
-                        lifetime_ms: new_particle.lifetime,
+                        lifetime_ms: new_particle.lifetime * scale,
+                        delay_ms: new_particle.delay * scale,
                     },
                 )
                 .expect("Unable to insert lifetime");
+            if new_particle.delay > 0.0 {
+                hidden
+                    .insert(p, Hidden {})
+                    .expect("Unable to hide particle");
+            }
         }
 
         particle_builder.requests.clear();
     }
 }
 
-pub fn cull_dead_particles(world: &mut World, ctx: &BTerm) {
+/// ages particles by the time the last frame took, deleting any that have expired
+/// and showing any whose delay has run out
+pub fn cull_dead_particles(world: &mut World, frame_time_ms: f32) {
     let mut dead_particles: Vec<Entity> = Vec::new();
     {
         // Age out particles
         let mut particles = world.write_storage::<ParticleLifetime>();
+        let mut hidden = world.write_storage::<Hidden>();
         let entities = world.entities();
         for (entity, mut particle) in (&entities, &mut particles).join() {
-            particle.lifetime_ms -= ctx.frame_time_ms;
+            if particle.delay_ms > 0.0 {
+                particle.delay_ms -= frame_time_ms;
+                if particle.delay_ms <= 0.0 {
+                    hidden.remove(entity);
+                }
+                continue;
+            }
+            particle.lifetime_ms -= frame_time_ms;
             if particle.lifetime_ms < 0.0 {
                 dead_particles.push(entity);
             }
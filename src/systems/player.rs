@@ -1,7 +1,11 @@
 use crate::components::{
-    CombatStats, EntityMoved, HungerClock, Player, Position, Viewshed, WantsToMelee,
+    CombatStats, EntityMoved, HungerClock, Player, Position, StatusEffects, StatusKind, Viewshed,
+    WantsToMelee,
+};
+use crate::{
+    gamelog::{GameLog, LogCategory},
+    HungerState, Map, Monster, RunState,
 };
-use crate::{HungerState, Map, Monster, RunState};
 use bracket_lib::prelude::*;
 use specs::prelude::*;
 use std::cmp::{max, min};
@@ -15,6 +19,11 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     let map = ecs.fetch::<Map>();
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
 
+    // standing still spends the turn without doing anything
+    if delta_x == 0 && delta_y == 0 {
+        return;
+    }
+
     for (entity, _player, pos, viewshed) in
         (&entities, &players, &mut positions, &mut viewshed).join()
     {
@@ -32,6 +41,9 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
 
         // if there is a target in front of the player, attempt to melee.
         for potential_target in map.tile_content[destination_idx].iter() {
+            if *potential_target == entity {
+                continue;
+            }
             let target = combat_stats.get(*potential_target);
             if let Some(_target) = target {
                 wants_to_melee
@@ -48,8 +60,8 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
 
         // if location isn't blocked, move to location and update viewshed.
         if !map.blocked[destination_idx] {
-            pos.x = min(79, max(0, pos.x + delta_x));
-            pos.y = min(49, max(0, pos.y + delta_y));
+            pos.x = min(map.width - 1, max(0, pos.x + delta_x));
+            pos.y = min(map.height - 1, max(0, pos.y + delta_y));
 
             viewshed.dirty = true;
             let mut player_pos = ecs.write_resource::<Point>();
@@ -61,6 +73,50 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     }
 }
 
+/// A confused player staggers off in a random direction instead of where they meant to go.
+/// The confusion itself wears off with the StatusSystem.
+pub fn stagger_if_confused(ecs: &mut World, delta_x: i32, delta_y: i32) -> (i32, i32) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let confused = ecs
+        .read_storage::<StatusEffects>()
+        .get(player_entity)
+        .map_or(false, |status| status.has(StatusKind::Confused));
+    if !confused {
+        return (delta_x, delta_y);
+    }
+
+    let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+    let mut gamelog = ecs.write_resource::<GameLog>();
+    gamelog.add(
+        LogCategory::General,
+        "You stumble around in confusion.".to_string(),
+    );
+    // always a step somewhere; never on the spot
+    loop {
+        let step = (rng.range(-1, 2), rng.range(-1, 2));
+        if step != (0, 0) {
+            return step;
+        }
+    }
+}
+
+/// Whether a paralysed player has to let this turn pass, whatever they asked for
+pub fn player_loses_turn(ecs: &mut World) -> bool {
+    let player_entity = *ecs.fetch::<Entity>();
+    let statuses = ecs.read_storage::<StatusEffects>();
+    let status = match statuses.get(player_entity) {
+        Some(status) => status,
+        None => return false,
+    };
+    if !status.loses_turn() {
+        return false;
+    }
+
+    let mut gamelog = ecs.write_resource::<GameLog>();
+    gamelog.add(LogCategory::General, "You can't move!".to_string());
+    true
+}
+
 pub fn skip_turn(world: &mut World) -> RunState {
     let player_entity = world.fetch::<Entity>();
     let viewshed_components = world.read_storage::<Viewshed>();
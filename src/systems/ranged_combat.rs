@@ -0,0 +1,260 @@
+use crate::{
+    components::{
+        Ammunition, BlocksTile, CombatStats, DefenseBonus, Equipped, InBackpack, Launcher, Name,
+        Position, Thrown, WantsToShoot,
+    },
+    effects::{EffectQueue, EffectType, Targets},
+    gamelog::{GameLog, LogCategory},
+    settings::{LogVerbosity, Settings},
+    systems::particle_system::ParticleBuilder,
+    Map,
+};
+use bracket_lib::prelude::*;
+use specs::prelude::*;
+
+/// how long a projectile takes to cross one tile
+const MS_PER_TILE: f32 = 40.0;
+
+pub struct RangedCombatSystem {}
+
+impl<'a> System<'a> for RangedCombatSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, WantsToShoot>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, CombatStats>,
+        WriteExpect<'a, EffectQueue>,
+        ReadStorage<'a, Launcher>,
+        ReadStorage<'a, Ammunition>,
+        ReadStorage<'a, Thrown>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, DefenseBonus>,
+        WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Position>,
+        ReadStorage<'a, BlocksTile>,
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, ParticleBuilder>,
+        ReadExpect<'a, Settings>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            mut log,
+            mut wants_shoot,
+            names,
+            combat_stats,
+            mut effects,
+            launchers,
+            ammunition,
+            thrown,
+            equipped,
+            defense_bonus,
+            mut backpack,
+            mut positions,
+            blockers,
+            map,
+            mut particle_builder,
+            settings,
+        ) = data;
+
+        for (entity, shot) in (&entities, &wants_shoot).join() {
+            let carried = backpack
+                .get(shot.item)
+                .map_or(false, |pack| pack.owner == entity);
+            if !carried {
+                continue;
+            }
+            let item_name = names
+                .get(shot.item)
+                .map_or("missile".to_string(), |name| name.name.clone());
+
+            // ammunition needs a matching launcher in hand, thrown items just need an arm
+            let (range, damage) = if let Some(ammo) = ammunition.get(shot.item) {
+                let launcher = (&launchers, &equipped)
+                    .join()
+                    .find(|(launcher, eq)| eq.owner == entity && launcher.ammo == ammo.kind);
+                match launcher {
+                    Some((launcher, _)) => (launcher.range, ammo.damage),
+                    None => {
+                        if entity == *player_entity {
+                            log.add(
+                                LogCategory::Item,
+                                format!("You have nothing to fire the {} with.", item_name),
+                            );
+                        }
+                        continue;
+                    }
+                }
+            } else if let Some(thrown) = thrown.get(shot.item) {
+                (thrown.range, thrown.damage)
+            } else {
+                continue;
+            };
+
+            let start = match positions.get(entity) {
+                Some(pos) => Point::new(pos.x, pos.y),
+                None => continue,
+            };
+
+            // walk the line of fire until it leaves range, meets a wall or strikes something
+            let mut landing = start;
+            let mut victim: Option<Entity> = None;
+            for (step, tile) in line2d_bresenham(start, shot.target)
+                .into_iter()
+                .skip(1)
+                .enumerate()
+            {
+                if tile.x < 0 || tile.x >= map.width || tile.y < 0 || tile.y >= map.height {
+                    break;
+                }
+                if DistanceAlg::Pythagoras.distance2d(start, tile) > range as f32 {
+                    break;
+                }
+                let idx = map.xy_idx(tile.x, tile.y);
+                if map.is_opaque(idx) {
+                    break;
+                }
+                landing = tile;
+                particle_builder.request_delayed(
+                    tile.x,
+                    tile.y,
+                    RGB::named(CYAN),
+                    RGB::named(BLACK),
+                    projectile_glyph(start, shot.target),
+                    MS_PER_TILE,
+                    step as f32 * MS_PER_TILE,
+                );
+                victim = map.tile_content[idx]
+                    .iter()
+                    .find(|e| blockers.get(**e).is_some() && combat_stats.get(**e).is_some())
+                    .copied();
+                if victim.is_some() {
+                    break;
+                }
+            }
+
+            // whatever was fired ends up on the floor where it stopped
+            backpack.remove(shot.item);
+            positions
+                .insert(
+                    shot.item,
+                    Position {
+                        x: landing.x,
+                        y: landing.y,
+                    },
+                )
+                .expect("Unable to place projectile");
+
+            match victim {
+                Some(victim) => {
+                    let target_stats = combat_stats.get(victim).unwrap();
+                    let mut defensive_bonus = 0;
+                    for (defense_bonus, equipped_by) in (&defense_bonus, &equipped).join() {
+                        if equipped_by.owner == victim {
+                            defensive_bonus += defense_bonus.defense;
+                        }
+                    }
+                    let defense = target_stats.defense + defensive_bonus;
+                    let dealt = i32::max(0, damage - defense);
+                    let shooter_name = names.get(entity).map_or("Something", |n| n.name.as_str());
+                    let victim_name = names.get(victim).map_or("something", |n| n.name.as_str());
+                    if dealt == 0 {
+                        if settings.log_verbosity != LogVerbosity::Brief {
+                            log.add(
+                                LogCategory::Combat,
+                                format!(
+                                    "{}'s {} glances off {}.",
+                                    shooter_name, item_name, victim_name
+                                ),
+                            );
+                        }
+                    } else {
+                        let mut message = format!(
+                            "{}'s {} hits {} for {} damage.",
+                            shooter_name, item_name, victim_name, dealt
+                        );
+                        if settings.log_verbosity == LogVerbosity::Verbose {
+                            message += &format!(" ({} damage vs {} defense)", damage, defense);
+                        }
+                        log.add(LogCategory::Combat, message);
+                        effects.add_effect(
+                            Some(entity),
+                            EffectType::Damage { amount: dealt },
+                            Targets::Single { target: victim },
+                        );
+                    }
+                }
+                None => {
+                    if entity == *player_entity {
+                        log.add(
+                            LogCategory::Combat,
+                            format!("The {} clatters to the floor.", item_name),
+                        );
+                    }
+                }
+            }
+        }
+        wants_shoot.clear();
+    }
+}
+
+/// a streak pointing the way the projectile is flying
+fn projectile_glyph(from: Point, to: Point) -> FontCharType {
+    let dx = to.x - from.x;
+    let dy = to.y - from.y;
+    if dy == 0 || i32::abs(dx) > 2 * i32::abs(dy) {
+        to_cp437('-')
+    } else if dx == 0 || i32::abs(dy) > 2 * i32::abs(dx) {
+        to_cp437('|')
+    } else if (dx > 0) == (dy > 0) {
+        to_cp437('\\')
+    } else {
+        to_cp437('/')
+    }
+}
+
+/// Finds the launcher the player has equipped and ammunition for it in their pack.
+/// Returns the launcher's range and the ammunition, or logs why there's nothing to fire.
+pub fn ready_to_fire(ecs: &World) -> Option<(i32, Entity)> {
+    let player_entity = ecs.fetch::<Entity>();
+    let entities = ecs.entities();
+    let launchers = ecs.read_storage::<Launcher>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let ammunition = ecs.read_storage::<Ammunition>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let mut log = ecs.write_resource::<GameLog>();
+
+    let launcher = (&launchers, &equipped)
+        .join()
+        .find(|(_launcher, eq)| eq.owner == *player_entity)
+        .map(|(launcher, _eq)| launcher);
+    let launcher = match launcher {
+        Some(launcher) => launcher,
+        None => {
+            log.add(
+                LogCategory::Item,
+                "You have nothing to fire with.".to_string(),
+            );
+            return None;
+        }
+    };
+
+    let ammo = (&entities, &ammunition, &backpack)
+        .join()
+        .find(|(_entity, ammo, pack)| pack.owner == *player_entity && ammo.kind == launcher.ammo)
+        .map(|(entity, _ammo, _pack)| entity);
+    match ammo {
+        Some(ammo) => Some((launcher.range, ammo)),
+        None => {
+            log.add(
+                LogCategory::Item,
+                format!("You are out of {}s.", launcher.ammo),
+            );
+            None
+        }
+    }
+}
@@ -1,6 +1,16 @@
 use crate::components::*;
+use crate::rng::{RngStream, RunSeed};
 use crate::World;
-use bracket_lib::terminal::Point;
+use crate::{
+    dungeon::MasterDungeonMap,
+    flow_field::FlowField,
+    game_world::{register_components, TurnCounter},
+    identification::MagicItemNames,
+    map::Map,
+};
+use bracket_lib::{random::RandomNumberGenerator, terminal::Point};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value};
 use specs::{
     prelude::*,
     saveload::{
@@ -8,61 +18,129 @@ use specs::{
         SimpleMarkerAllocator,
     },
 };
+#[cfg(not(target_arch = "wasm32"))]
 use std::fs::File;
-use std::path::Path;
-use std::{convert::Infallible, fs};
+use std::path::{Path, PathBuf};
+use std::{convert::Infallible, fmt, fs};
 
-macro_rules! serialize_individually {
-    ($ecs:expr, $ser:expr, $data:expr, $( $type:ty),*) => {
-        $(
-            SerializeComponents::<Infallible, SimpleMarker<SerializeMe>>::serialize(
-                &( $ecs.read_storage::<$type>(), ),
-                &$data.0,
-                &$data.1,
-                &mut $ser,
-            )
-            .unwrap();
-        )*
-    };
+/// where saves lived before there were slots
+const LEGACY_SAVE_PATH: &str = "./savegame.json";
+
+/// Bump this whenever the saved components change, and add a step to `migrate`
+/// so older saves can still be read.
+pub const SAVE_VERSION: u64 = 5;
+
+/// Component order of version 1 saves, which had no header and wrote each
+/// storage back to back. Frozen: don't change this when the components do.
+const V1_COMPONENTS: &[&str] = &[
+    "Position",
+    "Renderable",
+    "Player",
+    "Viewshed",
+    "Monster",
+    "Name",
+    "BlocksTile",
+    "CombatStats",
+    "SufferDamage",
+    "WantsToMelee",
+    "Item",
+    "Consumable",
+    "Ranged",
+    "InflictsDamage",
+    "AreaOfEffect",
+    "Confusion",
+    "ProvidesHealing",
+    "InBackpack",
+    "WantsToPickupItem",
+    "WantsToUseItem",
+    "WantsToDropItem",
+    "SerializationHelper",
+    "Equippable",
+    "Equipped",
+    "MeleePowerBonus",
+    "DefenseBonus",
+    "ParticleLifetime",
+    "HungerClock",
+    "HungerState",
+    "ProvidesFood",
+    "MagicMapper",
+    "Hidden",
+    "EntryTrigger",
+    "EntityMoved",
+    "SingleActivation",
+];
+
+/// What the load menu shows about a slot without loading the whole game
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SlotMeta {
+    pub character: String,
+    pub depth: i32,
+    pub turn: i32,
+    /// seconds since the unix epoch
+    pub timestamp: u64,
+    pub seed: u64,
 }
 
-macro_rules! deserialize_individually {
-    ($ecs:expr, $de:expr, $data:expr, $ ( $type:ty),*) => {
-        $(
-            DeserializeComponents::<Infallible, _>::deserialize(
-                &mut (&mut $ecs.write_storage::<$type>(), ),
-                &mut $data.0,
-                &mut $data.1,
-                &mut $data.2,
-                &mut $de,
-            )
-            .unwrap();
-        )*
-    };
+/// A save on disk. Meta is None for saves written before slots existed.
+#[derive(Clone, Debug)]
+pub struct SaveSlot {
+    pub name: String,
+    pub meta: Option<SlotMeta>,
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-pub fn save_game(world: &mut World) {
-    //helper
-    let mapcopy = world.get_mut::<crate::map::Map>().unwrap().clone();
-    let savehelper = world
-        .create_entity()
-        .with(SerializationHelper { map: mapcopy })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+/// The slot the current run saves into. None until the run is first saved.
+#[derive(Default)]
+pub struct ActiveSlot {
+    pub name: Option<String>,
+}
 
-    {
-        let data = (
-            world.entities(),
-            world.read_storage::<SimpleMarker<SerializeMe>>(),
-        );
+#[derive(Deserialize)]
+struct SaveHeader {
+    meta: Option<SlotMeta>,
+}
+
+/// Everything that can go wrong reading or writing a save
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    UnsupportedVersion(u64),
+    Corrupt(String),
+}
 
-        let writer = File::create("./savegame.json").unwrap();
-        let mut serializer = serde_json::Serializer::new(writer);
-        serialize_individually!(
-            world,
-            serializer,
-            data,
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "Unable to access save: {}", e),
+            SaveError::Json(e) => write!(f, "Save is corrupt: {}", e),
+            SaveError::UnsupportedVersion(v) => {
+                write!(f, "Save version {} is not supported", v)
+            }
+            SaveError::Corrupt(reason) => write!(f, "Save is corrupt: {}", reason),
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(e: std::io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(e: serde_json::Error) -> Self {
+        SaveError::Json(e)
+    }
+}
+
+/// The one list of components that get saved. Both save and load expand it,
+/// so a component can't end up written but never read back.
+macro_rules! with_saved_components {
+    ($action:ident, $ecs:expr, $components:expr, $data:expr) => {
+        $action!(
+            $ecs,
+            $components,
+            $data,
             Position,
             Renderable,
             Player,
@@ -71,7 +149,6 @@ pub fn save_game(world: &mut World) {
             Name,
             BlocksTile,
             CombatStats,
-            SufferDamage,
             WantsToMelee,
             Item,
             Consumable,
@@ -84,6 +161,7 @@ pub fn save_game(world: &mut World) {
             WantsToPickupItem,
             WantsToUseItem,
             WantsToDropItem,
+            WantsToRemoveItem,
             SerializationHelper,
             Equippable,
             Equipped,
@@ -94,19 +172,279 @@ pub fn save_game(world: &mut World) {
             HungerState,
             ProvidesFood,
             MagicMapper,
+            Teleports,
             Hidden,
             EntryTrigger,
             EntityMoved,
-            SingleActivation
+            SingleActivation,
+            Experience,
+            ProvidesXp,
+            Launcher,
+            Ammunition,
+            Thrown,
+            WantsToShoot,
+            AiState,
+            OtherLevelPosition,
+            StatusEffects,
+            InflictsStatus,
+            Initiative,
+            Enchantment,
+            Cursed,
+            UnknownEnchantment,
+            Identifies,
+            Weight,
+            Stackable
+        )
+    };
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+macro_rules! serialize_individually {
+    ($ecs:expr, $components:expr, $data:expr, $( $type:ty),*) => {
+        $(
+            $components.insert(
+                stringify!($type).to_string(),
+                SerializeComponents::<Infallible, SimpleMarker<SerializeMe>>::serialize(
+                    &( $ecs.read_storage::<$type>(), ),
+                    &$data.0,
+                    &$data.1,
+                    serde_json::value::Serializer,
+                )?,
+            );
+        )*
+    };
+}
+
+macro_rules! deserialize_individually {
+    ($ecs:expr, $components:expr, $data:expr, $( $type:ty),*) => {
+        $(
+            // components added after the save was written simply aren't there
+            if let Some(value) = $components.remove(stringify!($type)) {
+                DeserializeComponents::<Infallible, _>::deserialize(
+                    &mut (&mut $ecs.write_storage::<$type>(), ),
+                    &mut $data.0,
+                    &mut $data.1,
+                    &mut $data.2,
+                    value,
+                )?;
+            }
+        )*
+    };
+}
+
+/// Saves into the run's slot, picking a free one the first time the run is saved
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_game(world: &mut World) -> Result<(), SaveError> {
+    let slot = world
+        .fetch::<ActiveSlot>()
+        .name
+        .clone()
+        .unwrap_or_else(next_free_slot_name);
+
+    //helper
+    let mapcopy = world.get_mut::<Map>().unwrap().clone();
+    let seed = *world.fetch::<RunSeed>();
+    let turn = world.fetch::<TurnCounter>().turn;
+    let dungeon = world.fetch::<MasterDungeonMap>().clone();
+    let magic_items = world.fetch::<MagicItemNames>().clone();
+    let meta = SlotMeta {
+        character: player_name(world),
+        depth: mapcopy.depth,
+        turn,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0),
+        seed: seed.seed,
+    };
+    let savehelper = world
+        .create_entity()
+        .with(SerializationHelper {
+            map: mapcopy,
+            seed,
+            turn,
+            dungeon,
+            magic_items,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+
+    let result = write_save(world, &slot, &meta);
+    world.delete_entity(savehelper).expect("Crash on cleanup");
+    if result.is_ok() {
+        world.fetch_mut::<ActiveSlot>().name = Some(slot);
+    }
+    result
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn player_name(world: &World) -> String {
+    let player_entity = world.fetch::<Entity>();
+    world
+        .read_storage::<Name>()
+        .get(*player_entity)
+        .map(|name| name.name.clone())
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_save(world: &World, slot: &str, meta: &SlotMeta) -> Result<(), SaveError> {
+    let mut components = JsonMap::new();
+    {
+        let data = (
+            world.entities(),
+            world.read_storage::<SimpleMarker<SerializeMe>>(),
         );
+        with_saved_components!(serialize_individually, world, components, data);
     }
-    world.delete_entity(savehelper).expect("Crash on cleanup");
+
+    fs::create_dir_all(save_dir())?;
+    let writer = File::create(slot_path(slot))?;
+    serde_json::to_writer(
+        writer,
+        &serde_json::json!({ "version": SAVE_VERSION, "meta": meta, "components": components }),
+    )?;
+    Ok(())
 }
 
 #[cfg(target_arch = "wasm32")]
-pub fn save_game(_world: &mut World) {}
+pub fn save_game(_world: &mut World) -> Result<(), SaveError> {
+    Ok(())
+}
+
+/// Reads a save, upgrading it to the current version if needed.
+/// Returns each component's saved storage, keyed by component name.
+fn read_save(data: &str) -> Result<JsonMap<String, Value>, SaveError> {
+    let values = serde_json::Deserializer::from_str(data)
+        .into_iter::<Value>()
+        .collect::<Result<Vec<Value>, _>>()?;
+
+    let (version, mut components) = match values.as_slice() {
+        [Value::Object(save)] if save.contains_key("version") => {
+            let version = save
+                .get("version")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| SaveError::Corrupt("bad version".to_string()))?;
+            let components = match save.get("components") {
+                Some(Value::Object(components)) => components.clone(),
+                _ => return Err(SaveError::Corrupt("no components".to_string())),
+            };
+            (version, components)
+        }
+        _ => {
+            if values.len() > V1_COMPONENTS.len() {
+                return Err(SaveError::Corrupt("unrecognised format".to_string()));
+            }
+            let components = V1_COMPONENTS
+                .iter()
+                .map(|name| name.to_string())
+                .zip(values)
+                .collect();
+            (1, components)
+        }
+    };
+
+    if version > SAVE_VERSION {
+        return Err(SaveError::UnsupportedVersion(version));
+    }
+    for from in version..SAVE_VERSION {
+        migrate(from, &mut components)?;
+    }
+
+    if !components.contains_key("SerializationHelper") {
+        return Err(SaveError::Corrupt("no map".to_string()));
+    }
+    Ok(components)
+}
+
+/// Upgrades saved components from version `from` to `from + 1`
+fn migrate(from: u64, components: &mut JsonMap<String, Value>) -> Result<(), SaveError> {
+    match from {
+        // read_save has already split the headerless stream into named components,
+        // which is all version 1 needs
+        1 => Ok(()),
+        // damage started tracking who dealt it and is no longer saved
+        2 => {
+            components.remove("SufferDamage");
+            Ok(())
+        }
+        // particles gained a delay; they only last a moment, so old ones are dropped.
+        // The helper gained the dungeon's levels and the names of magic items.
+        3 => {
+            components.remove("ParticleLifetime");
+            let dungeon = serde_json::to_value(MasterDungeonMap::default())?;
+            let magic_items = serde_json::to_value(MagicItemNames::default())?;
+            edit_saved(components, "SerializationHelper", |helper| {
+                helper.entry("dungeon").or_insert_with(|| dungeon.clone());
+                helper
+                    .entry("magic_items")
+                    .or_insert_with(|| magic_items.clone());
+            });
+            Ok(())
+        }
+        // stacks started counting their items; before that every item was its own entity
+        4 => {
+            edit_saved(components, "Stackable", |stack| {
+                stack.entry("quantity").or_insert(Value::from(1));
+            });
+            Ok(())
+        }
+        _ => Err(SaveError::UnsupportedVersion(from)),
+    }
+}
+
+/// Runs edit on the saved data of each instance of the named component
+fn edit_saved(
+    components: &mut JsonMap<String, Value>,
+    name: &str,
+    mut edit: impl FnMut(&mut JsonMap<String, Value>),
+) {
+    if let Some(Value::Array(saved)) = components.get_mut(name) {
+        for entity in saved.iter_mut() {
+            // each entity is saved as { marker, components: [data] }
+            if let Some(Value::Object(data)) = entity.pointer_mut("/components/0") {
+                edit(data);
+            }
+        }
+    }
+}
+
+/// Loads the components into a world of their own, so a save that can't be loaded
+/// is caught before anything in the real world is deleted
+fn check_components(mut components: JsonMap<String, Value>) -> Result<(), SaveError> {
+    let mut scratch = World::new();
+    register_components(&mut scratch);
+    scratch.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+    {
+        let mut d = (
+            &mut scratch.entities(),
+            &mut scratch.write_storage::<SimpleMarker<SerializeMe>>(),
+            &mut scratch.write_resource::<SimpleMarkerAllocator<SerializeMe>>(),
+        );
+
+        with_saved_components!(deserialize_individually, scratch, components, d);
+    }
+
+    if (&scratch.read_storage::<SerializationHelper>())
+        .join()
+        .next()
+        .is_none()
+    {
+        return Err(SaveError::Corrupt("no map".to_string()));
+    }
+    if (&scratch.read_storage::<Player>()).join().next().is_none() {
+        return Err(SaveError::Corrupt("no player".to_string()));
+    }
+    Ok(())
+}
+
+/// Replaces the world with the saved game. The save is read and loaded into a scratch
+/// world before anything is deleted, so a bad file leaves the current world alone.
+pub fn load_game(world: &mut World, slot: &str) -> Result<(), SaveError> {
+    let data = fs::read_to_string(slot_path(slot))?;
+    let mut components = read_save(&data)?;
+    check_components(components.clone())?;
 
-pub fn load_game(world: &mut World) {
     // Delete everything
     {
         let mut to_delete = Vec::new();
@@ -117,9 +455,6 @@ pub fn load_game(world: &mut World) {
             world.delete_entity(*del).expect("Delete failed");
         }
 
-        let data = fs::read_to_string("./savegame.json").unwrap();
-        let mut de = serde_json::Deserializer::from_str(&data);
-
         {
             let mut d = (
                 &mut world.entities(),
@@ -127,37 +462,7 @@ pub fn load_game(world: &mut World) {
                 &mut world.write_resource::<SimpleMarkerAllocator<SerializeMe>>(),
             );
 
-            deserialize_individually!(
-                world,
-                de,
-                d,
-                Position,
-                Renderable,
-                Player,
-                Viewshed,
-                Monster,
-                Name,
-                BlocksTile,
-                CombatStats,
-                SufferDamage,
-                WantsToMelee,
-                Item,
-                Consumable,
-                Ranged,
-                InflictsDamage,
-                AreaOfEffect,
-                Confusion,
-                ProvidesHealing,
-                InBackpack,
-                WantsToPickupItem,
-                WantsToUseItem,
-                WantsToDropItem,
-                SerializationHelper,
-                Equippable,
-                Equipped,
-                MeleePowerBonus,
-                DefenseBonus
-            );
+            with_saved_components!(deserialize_individually, world, components, d);
         }
 
         let mut deleteme: Option<Entity> = None;
@@ -169,9 +474,17 @@ pub fn load_game(world: &mut World) {
             let position = world.read_storage::<Position>();
 
             for (entity, helper) in (&entities, &helper).join() {
-                let mut worldmap = world.write_resource::<crate::map::Map>();
+                let mut worldmap = world.write_resource::<Map>();
                 *worldmap = helper.map.clone();
-                worldmap.tile_content = vec![Vec::new(); crate::map::MAP_COUNT];
+                worldmap.tile_content = vec![Vec::new(); worldmap.tiles.len()];
+
+                // the exact combat stream position isn't saved, so pick the level's stream back up from its start
+                *world.write_resource::<RunSeed>() = helper.seed;
+                world.write_resource::<TurnCounter>().turn = helper.turn;
+                *world.write_resource::<MasterDungeonMap>() = helper.dungeon.clone();
+                *world.write_resource::<MagicItemNames>() = helper.magic_items.clone();
+                *world.write_resource::<RandomNumberGenerator>() =
+                    helper.seed.stream(worldmap.depth, RngStream::Combat);
                 deleteme = Some(entity);
             }
             for (entity, _player, pos) in (&entities, &player, &position).join() {
@@ -181,17 +494,224 @@ pub fn load_game(world: &mut World) {
                 *player_resource = entity;
             }
         }
-        world
-            .delete_entity(deleteme.unwrap())
-            .expect("Unable to delete helper");
+        match deleteme {
+            Some(helper) => world
+                .delete_entity(helper)
+                .expect("Unable to delete helper"),
+            None => return Err(SaveError::Corrupt("no map".to_string())),
+        }
+    }
+    world.fetch_mut::<ActiveSlot>().name = Some(slot.to_string());
+    world.insert(FlowField::default());
+    Ok(())
+}
+
+/// The per-user data directory saves go in, e.g. ~/.local/share/khazad-dum/saves.
+/// Falls back to ./saves when the platform doesn't say where that is.
+pub fn save_dir() -> PathBuf {
+    let base = if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| {
+            PathBuf::from(home)
+                .join("Library")
+                .join("Application Support")
+        })
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .map(|home| PathBuf::from(home).join(".local").join("share"))
+            })
+    };
+    match base {
+        Some(base) => base.join("khazad-dum").join("saves"),
+        None => PathBuf::from("./saves"),
     }
 }
 
-pub fn delete_save() {
-    if Path::new("./savegame.json").exists() {
-        std::fs::remove_file("./savegame.json").expect("Unable to delete file")
+fn slot_path(slot: &str) -> PathBuf {
+    save_dir().join(format!("{}.json", slot))
+}
+
+/// the first of slot1, slot2... that isn't already on disk
+fn next_free_slot_name() -> String {
+    (1..)
+        .map(|n| format!("slot{}", n))
+        .find(|name| !slot_path(name).exists())
+        .expect("Ran out of save slots")
+}
+
+fn slot_names() -> Vec<String> {
+    let mut names: Vec<String> = match fs::read_dir(save_dir()) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+            .filter_map(|path| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    names.sort();
+    names
+}
+
+/// Every save on disk, newest first.
+/// A save from before slots existed is moved into a slot of its own first.
+pub fn list_slots() -> Vec<SaveSlot> {
+    import_legacy_save();
+    let mut slots: Vec<SaveSlot> = slot_names()
+        .into_iter()
+        .map(|name| {
+            let meta = fs::read_to_string(slot_path(&name))
+                .ok()
+                .and_then(|data| serde_json::from_str::<SaveHeader>(&data).ok())
+                .and_then(|header| header.meta);
+            SaveSlot { name, meta }
+        })
+        .collect();
+    slots
+        .sort_by_key(|slot| std::cmp::Reverse(slot.meta.as_ref().map_or(0, |meta| meta.timestamp)));
+    slots
+}
+
+fn import_legacy_save() {
+    if !Path::new(LEGACY_SAVE_PATH).exists() {
+        return;
+    }
+    let target = slot_path(&next_free_slot_name());
+    let moved = fs::create_dir_all(save_dir())
+        .and_then(|_| fs::copy(LEGACY_SAVE_PATH, &target))
+        .and_then(|_| fs::remove_file(LEGACY_SAVE_PATH));
+    if let Err(e) = moved {
+        bracket_lib::prelude::console::log(format!("Unable to move old save into a slot: {}", e));
     }
 }
+
+pub fn delete_slot(slot: &str) -> Result<(), SaveError> {
+    fs::remove_file(slot_path(slot))?;
+    Ok(())
+}
+
 pub fn does_save_exist() -> bool {
-    Path::new("./savegame.json").exists()
+    Path::new(LEGACY_SAVE_PATH).exists() || !slot_names().is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// a version 1 save: every storage in V1_COMPONENTS order, with no header
+    fn v1_save() -> String {
+        (0..V1_COMPONENTS.len())
+            .map(|i| json!([{ "marker": [i], "components": [i] }]).to_string())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn version_1_storages_are_named_in_order() {
+        let components = read_save(&v1_save()).expect("Unable to read v1 save");
+        assert_eq!(
+            components["Position"],
+            json!([{ "marker": [0], "components": [0] }])
+        );
+        let helper = V1_COMPONENTS
+            .iter()
+            .position(|name| *name == "SerializationHelper")
+            .unwrap();
+        assert_eq!(
+            components["SerializationHelper"],
+            json!([{ "marker": [helper], "components": [helper] }])
+        );
+        assert_eq!(
+            components["SingleActivation"],
+            json!([{ "marker": [V1_COMPONENTS.len() - 1], "components": [V1_COMPONENTS.len() - 1] }])
+        );
+    }
+
+    #[test]
+    fn version_1_is_migrated_to_the_current_components() {
+        let components = read_save(&v1_save()).expect("Unable to read v1 save");
+        assert!(!components.contains_key("SufferDamage"));
+        assert!(!components.contains_key("ParticleLifetime"));
+        assert!(components.contains_key("HungerClock"));
+    }
+
+    #[test]
+    fn version_1_with_too_many_storages_is_corrupt() {
+        let data = format!("{}\n[]", v1_save());
+        assert!(matches!(read_save(&data), Err(SaveError::Corrupt(_))));
+    }
+
+    #[test]
+    fn version_2_is_migrated_to_the_current_components() {
+        let data = json!({
+            "version": 2,
+            "components": {
+                "SufferDamage": [{ "marker": [0], "components": [{ "amount": [3] }] }],
+                "ParticleLifetime": [{ "marker": [1], "components": [{ "lifetime_ms": 200.0 }] }],
+                "SerializationHelper": [{ "marker": [2], "components": [{ "map": {}, "turn": 7 }] }],
+                "Stackable": [{ "marker": [3], "components": [{}] }],
+            }
+        })
+        .to_string();
+        let components = read_save(&data).expect("Unable to read v2 save");
+
+        assert!(!components.contains_key("SufferDamage"));
+        assert!(!components.contains_key("ParticleLifetime"));
+        let helper = &components["SerializationHelper"][0]["components"][0];
+        assert_eq!(helper["turn"], json!(7));
+        assert!(helper.get("dungeon").is_some());
+        assert!(helper.get("magic_items").is_some());
+        assert_eq!(
+            components["Stackable"][0]["components"][0],
+            json!({ "quantity": 1 })
+        );
+    }
+
+    #[test]
+    fn current_version_is_left_alone() {
+        let data = json!({
+            "version": SAVE_VERSION,
+            "components": {
+                "SerializationHelper": [{ "marker": [0], "components": [{ "map": {} }] }],
+                "Stackable": [{ "marker": [1], "components": [{ "quantity": 4 }] }],
+            }
+        })
+        .to_string();
+        let components = read_save(&data).expect("Unable to read save");
+        assert_eq!(
+            components["Stackable"][0]["components"][0],
+            json!({ "quantity": 4 })
+        );
+        assert!(components["SerializationHelper"][0]["components"][0]
+            .get("dungeon")
+            .is_none());
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let data = json!({
+            "version": SAVE_VERSION + 1,
+            "components": { "SerializationHelper": [] }
+        })
+        .to_string();
+        assert!(matches!(
+            read_save(&data),
+            Err(SaveError::UnsupportedVersion(v)) if v == SAVE_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn a_save_without_a_map_is_corrupt() {
+        let data = json!({ "version": SAVE_VERSION, "components": {} }).to_string();
+        assert!(matches!(read_save(&data), Err(SaveError::Corrupt(_))));
+    }
 }
@@ -1,67 +1,62 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::entities::*;
 use crate::map::*;
-use crate::systems::spawn_table::RandomTable;
+use crate::raws::{get_spawn_table_for_depth, raws, spawn_named_entity, SpawnType};
 use crate::*;
-fn room_table(map_depth: i32) -> RandomTable {
-    RandomTable::new()
-        .add("Goblin", 10)
-        .add("Orc", 1 + map_depth)
-        .add("Health Potion", 7)
-        .add("Fireball Scroll", 2 + map_depth)
-        .add("Confusion Scroll", 2 + map_depth)
-        .add("Magic Missile Scroll", 4)
-        .add("Dagger", 3)
-        .add("Shield", 3)
-        .add("Rations", 10)
-        .add("Magic Mapping Scroll", 2)
-        .add("Bear Trap", 200)
+
+/// Fills a room with monsters, items & traps
+pub fn spawn_room(map: &Map, world: &mut World, room: &Rectangle, map_depth: i32) {
+    let mut possible_targets: Vec<usize> = Vec::new();
+    for y in room.y1 + 1..=room.y2 {
+        for x in room.x1 + 1..=room.x2 {
+            let idx = map.xy_idx(x, y);
+            if map.tiles[idx] == TileType::Floor {
+                possible_targets.push(idx);
+            }
+        }
+    }
+
+    spawn_region(map, world, &possible_targets, map_depth);
 }
 
-pub fn spawn_room(world: &mut World, room: &Rectangle, map_depth: i32) {
-    let spawn_table = room_table(map_depth);
-    let mut spawn_points: HashMap<usize, String> = HashMap::new();
+/// Fills an arbitrary set of tiles with monsters, items & traps.
+/// Used by builders that don't have rooms, like caves.
+pub fn spawn_region(map: &Map, world: &mut World, area: &[usize], map_depth: i32) {
+    let spawn_table = get_spawn_table_for_depth(raws(), map_depth);
+    let mut spawn_points: BTreeMap<usize, String> = BTreeMap::new();
+    let mut areas: Vec<usize> = Vec::from(area);
 
     {
         let mut rng = world.write_resource::<RandomNumberGenerator>();
-        let num_spawns = rng.roll_dice(1, MAX_MOBS + 15) + (map_depth - 1) - 3;
+        let num_spawns = i32::min(
+            areas.len() as i32,
+            rng.roll_dice(1, MAX_MOBS + 15) + (map_depth - 1) - 3,
+        );
 
         for _i in 0..num_spawns {
-            let mut added = false;
-            let mut tries = 0;
-            while !added && tries < 50 {
-                let x = (room.x1 + rng.roll_dice(1, i32::abs(room.x2 - room.x1))) as usize;
-                let y = (room.y1 + rng.roll_dice(1, i32::abs(room.y2 - room.y1))) as usize;
-                let idx = (y * MAP_WIDTH) + x;
-
-                if !spawn_points.contains_key(&idx) {
-                    spawn_points.insert(idx, spawn_table.roll(&mut rng));
-                    added = true;
-                } else {
-                    tries += 1;
-                }
-            }
+            let array_index = if areas.len() == 1 {
+                0usize
+            } else {
+                (rng.roll_dice(1, areas.len() as i32) - 1) as usize
+            };
+            let map_idx = areas[array_index];
+            spawn_points.insert(map_idx, spawn_table.roll(&mut rng));
+            areas.remove(array_index);
         }
     }
 
     for spawn in spawn_points.iter() {
-        let x = (*spawn.0 % MAP_WIDTH) as i32;
-        let y = (*spawn.0 / MAP_WIDTH) as i32;
+        let x = *spawn.0 as i32 % map.width;
+        let y = *spawn.0 as i32 / map.width;
 
-        match spawn.1.as_ref() {
-            "Goblin" => create_goblin(world, x, y),
-            "Orc" => create_ogre(world, x, y),
-            "Health Potion" => health_potion(world, x, y),
-            "Fireball Scroll" => fireball_scroll(world, x, y),
-            "Confusion Scroll" => confusion_scroll(world, x, y),
-            "Magic Missile Scroll" => magic_missile_scroll(world, x, y),
-            "Dagger" => dagger(world, x, y),
-            "Shield" => shield(world, x, y),
-            "Rations" => ration(world, x, y),
-            "Magic Mapping Scroll" => magic_mapping_scroll(world, x, y),
-            "Bear Trap" => bear_trap(world, x, y),
-            _ => {}
+        if spawn_named_entity(raws(), world, spawn.1, SpawnType::AtPosition { x, y }).is_none()
+            && spawn.1 != "None"
+        {
+            console::log(format!(
+                "WARNING: nothing in the raws is called {}",
+                spawn.1
+            ));
         }
     }
 }
@@ -0,0 +1,64 @@
+use crate::{
+    effects::{EffectQueue, EffectType, Targets},
+    gamelog::{GameLog, LogCategory},
+    MyTurn, StatusEffects, StatusKind,
+};
+use specs::prelude::*;
+
+/// Ticks every status down once per turn of whoever is under it.
+/// Poison and regeneration act on each of those turns, before the duration runs out.
+pub struct StatusSystem {}
+
+impl<'a> System<'a> for StatusSystem {
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        Entities<'a>,
+        WriteStorage<'a, StatusEffects>,
+        ReadStorage<'a, MyTurn>,
+        WriteExpect<'a, EffectQueue>,
+        WriteExpect<'a, GameLog>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (player_entity, entities, mut statuses, turns, mut effects, mut log) = data;
+
+        let mut cured = Vec::new();
+        // statuses only wear off on the turns of whoever is under them
+        for (entity, status, _turn) in (&entities, &mut statuses, &turns).join() {
+            for effect in status.effects.iter_mut() {
+                match effect.kind {
+                    StatusKind::Poisoned => effects.add_effect(
+                        None,
+                        EffectType::Damage {
+                            amount: effect.potency,
+                        },
+                        Targets::Single { target: entity },
+                    ),
+                    StatusKind::Regenerating => effects.add_effect(
+                        None,
+                        EffectType::Healing {
+                            amount: effect.potency,
+                        },
+                        Targets::Single { target: entity },
+                    ),
+                    _ => {}
+                }
+                effect.turns -= 1;
+                if effect.turns < 1 && entity == *player_entity {
+                    log.add(
+                        LogCategory::General,
+                        format!("You are no longer {}.", effect.kind.adjective()),
+                    );
+                }
+            }
+            status.effects.retain(|effect| effect.turns > 0);
+            if status.effects.is_empty() {
+                cured.push(entity);
+            }
+        }
+
+        for entity in cured {
+            statuses.remove(entity);
+        }
+    }
+}
@@ -0,0 +1,269 @@
+// module for walking the player somewhere over many turns: auto-explore and travel
+use crate::{
+    components::{CombatStats, Hidden, Item, Monster, Name, Position},
+    flow_field::Frontier,
+    game_world::PlayerCommand,
+    gamelog::{GameLog, LogCategory},
+    identification::display_name,
+    map::{Map, TileType},
+    Point,
+};
+use bracket_lib::prelude::BaseMap;
+use specs::prelude::*;
+use std::collections::{BinaryHeap, HashSet};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Destination {
+    /// the nearest tile the player hasn't seen, again and again until there are none left
+    Unexplored,
+    Tile {
+        x: i32,
+        y: i32,
+    },
+}
+
+/// Where the player is being walked to, one step a turn, until something needs their attention
+#[derive(Default)]
+pub struct Travel {
+    destination: Option<Destination>,
+    /// the player's hit points as of the last step, so any damage stops them
+    hp: i32,
+    /// items already in sight, which don't stop the player a second time
+    seen_items: HashSet<Entity>,
+}
+
+impl Travel {
+    pub fn is_travelling(&self) -> bool {
+        self.destination.is_some()
+    }
+
+    pub fn stop(&mut self) {
+        self.destination = None;
+    }
+}
+
+/// Sets the player off towards the destination, unless there's a monster in sight.
+pub fn start_travel(ecs: &mut World, destination: Destination) {
+    if let Some(name) = monster_in_view(ecs) {
+        ecs.write_resource::<GameLog>().add(
+            LogCategory::General,
+            format!("Not with the {} in sight!", name),
+        );
+        return;
+    }
+    let hp = player_hp(ecs);
+    let seen_items = items_in_view(ecs).into_iter().collect();
+    *ecs.write_resource::<Travel>() = Travel {
+        destination: Some(destination),
+        hp,
+        seen_items,
+    };
+}
+
+/// travels to the stairs down, if the player has seen them
+pub fn travel_to_stairs(ecs: &mut World) {
+    let stairs = {
+        let map = ecs.fetch::<Map>();
+        map.tiles
+            .iter()
+            .enumerate()
+            .position(|(idx, tile)| *tile == TileType::DownStairs && map.revealed_tiles[idx])
+            .map(|idx| (idx as i32 % map.width, idx as i32 / map.width))
+    };
+    match stairs {
+        Some((x, y)) => start_travel(ecs, Destination::Tile { x, y }),
+        None => ecs.write_resource::<GameLog>().add(
+            LogCategory::General,
+            "You haven't found the way down yet.".to_string(),
+        ),
+    }
+}
+
+/// The player's next move, or None once they've stopped: because they arrived,
+/// there's no way on, or something came up that they should see to.
+pub fn travel_step(ecs: &mut World) -> Option<PlayerCommand> {
+    let destination = ecs.fetch::<Travel>().destination?;
+    if let Some(reason) = interruption(ecs) {
+        stop_travel(ecs, Some(reason));
+        return None;
+    }
+
+    let player_pos = *ecs.fetch::<Point>();
+    let (arrived, step, width) = {
+        let map = ecs.fetch::<Map>();
+        let start = map.xy_idx(player_pos.x, player_pos.y);
+        match destination {
+            Destination::Unexplored => (
+                false,
+                first_step(&map, start, |idx| !map.revealed_tiles[idx]),
+                map.width,
+            ),
+            Destination::Tile { x, y } => {
+                let goal = map.xy_idx(x, y);
+                if goal == start {
+                    (true, None, map.width)
+                } else {
+                    (false, first_step(&map, start, |idx| idx == goal), map.width)
+                }
+            }
+        }
+    };
+    if arrived {
+        stop_travel(ecs, None);
+        return None;
+    }
+
+    match step {
+        Some(idx) => Some(move_to(idx, width, player_pos)),
+        None => {
+            let reason = match destination {
+                Destination::Unexplored => "There's nowhere left to explore.",
+                Destination::Tile { .. } => "You can't find a way there.",
+            };
+            stop_travel(ecs, Some(reason.to_string()));
+            None
+        }
+    }
+}
+
+/// Sends the player to a tile. With a monster in sight travel won't start, so they only
+/// get the first step of the way, and each click brings them one tile closer.
+pub fn walk_to(ecs: &mut World, x: i32, y: i32) -> Option<PlayerCommand> {
+    if monster_in_view(ecs).is_none() {
+        start_travel(ecs, Destination::Tile { x, y });
+        return None;
+    }
+
+    let player_pos = *ecs.fetch::<Point>();
+    let (step, width) = {
+        let map = ecs.fetch::<Map>();
+        let goal = map.xy_idx(x, y);
+        let start = map.xy_idx(player_pos.x, player_pos.y);
+        (first_step(&map, start, |idx| idx == goal), map.width)
+    };
+    if step.is_none() {
+        ecs.write_resource::<GameLog>().add(
+            LogCategory::General,
+            "You can't find a way there.".to_string(),
+        );
+    }
+    step.map(|idx| move_to(idx, width, player_pos))
+}
+
+/// ends the trip, with a message if there's a reason worth telling the player
+pub fn stop_travel(ecs: &mut World, reason: Option<String>) {
+    ecs.write_resource::<Travel>().stop();
+    if let Some(reason) = reason {
+        ecs.write_resource::<GameLog>()
+            .add(LogCategory::General, reason);
+    }
+}
+
+/// why the player should stop here: they've been hurt, or something new has come into view
+fn interruption(ecs: &World) -> Option<String> {
+    let hp = player_hp(ecs);
+    let mut travel = ecs.write_resource::<Travel>();
+    if hp < travel.hp {
+        return Some("You stop, hurt.".to_string());
+    }
+    travel.hp = hp;
+
+    if let Some(name) = monster_in_view(ecs) {
+        return Some(format!("You see a {} and stop.", name));
+    }
+    for item in items_in_view(ecs) {
+        if travel.seen_items.insert(item) {
+            return Some(format!("You spot a {}.", display_name(ecs, item)));
+        }
+    }
+    None
+}
+
+fn player_hp(ecs: &World) -> i32 {
+    let player_entity = *ecs.fetch::<Entity>();
+    ecs.read_storage::<CombatStats>()
+        .get(player_entity)
+        .map_or(0, |stats| stats.hp)
+}
+
+/// the name of a monster the player can see, if there is one
+fn monster_in_view(ecs: &World) -> Option<String> {
+    let map = ecs.fetch::<Map>();
+    let monsters = ecs.read_storage::<Monster>();
+    let positions = ecs.read_storage::<Position>();
+    let names = ecs.read_storage::<Name>();
+    let seen = (&monsters, &positions, &names)
+        .join()
+        .find(|(_monster, pos, _name)| map.visible_tiles[map.xy_idx(pos.x, pos.y)])
+        .map(|(_monster, _pos, name)| name.name.clone());
+    seen
+}
+
+fn items_in_view(ecs: &World) -> Vec<Entity> {
+    let map = ecs.fetch::<Map>();
+    let entities = ecs.entities();
+    let items = ecs.read_storage::<Item>();
+    let positions = ecs.read_storage::<Position>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let seen = (&entities, &items, &positions, !&hidden)
+        .join()
+        .filter(|(_entity, _item, pos, _hidden)| map.visible_tiles[map.xy_idx(pos.x, pos.y)])
+        .map(|(entity, _item, _pos, _hidden)| entity)
+        .collect();
+    seen
+}
+
+/// the move that takes the player from where they are onto the map index next to them
+fn move_to(idx: usize, width: i32, player_pos: Point) -> PlayerCommand {
+    PlayerCommand::Move {
+        dx: idx as i32 % width - player_pos.x,
+        dy: idx as i32 / width - player_pos.y,
+    }
+}
+
+/// Searches outwards over the tiles the player has seen for the nearest goal, and returns
+/// the first tile on the way there. Unseen tiles can be goals but aren't walked through.
+fn first_step(map: &Map, start: usize, is_goal: impl Fn(usize) -> bool) -> Option<usize> {
+    let mut costs = vec![f32::MAX; map.tiles.len()];
+    let mut came_from: Vec<Option<usize>> = vec![None; map.tiles.len()];
+    let mut frontier = BinaryHeap::new();
+    costs[start] = 0.0;
+    frontier.push(Frontier {
+        cost: 0.0,
+        idx: start,
+    });
+
+    while let Some(Frontier { cost, idx }) = frontier.pop() {
+        if cost > costs[idx] {
+            continue;
+        }
+        if idx != start {
+            if is_goal(idx) {
+                // walk the path back to the step just after the start
+                let mut step = idx;
+                while let Some(previous) = came_from[step] {
+                    if previous == start {
+                        return Some(step);
+                    }
+                    step = previous;
+                }
+                return None;
+            }
+            if !map.revealed_tiles[idx] {
+                continue;
+            }
+        }
+        for (exit, step_cost) in map.get_available_exits(idx) {
+            let next_cost = cost + step_cost;
+            if next_cost < costs[exit] {
+                costs[exit] = next_cost;
+                came_from[exit] = Some(idx);
+                frontier.push(Frontier {
+                    cost: next_cost,
+                    idx: exit,
+                });
+            }
+        }
+    }
+    None
+}
@@ -1,12 +1,11 @@
 use crate::{
-    gamelog::GameLog, EntityMoved, EntryTrigger, Hidden, InflictsDamage, Map, Name, Position,
-    SingleActivation, SufferDamage,
+    effects::{EffectQueue, EffectType, Targets},
+    gamelog::{GameLog, LogCategory},
+    EntityMoved, EntryTrigger, Hidden, InflictsDamage, Map, Name, Position, SingleActivation,
 };
 use bracket_lib::terminal::*;
 use specs::prelude::*;
 
-use super::particle_system::ParticleBuilder;
-
 pub struct TriggerSystem {}
 
 impl<'a> System<'a> for TriggerSystem {
@@ -20,8 +19,7 @@ impl<'a> System<'a> for TriggerSystem {
         Entities<'a>,
         WriteExpect<'a, GameLog>,
         ReadStorage<'a, InflictsDamage>,
-        WriteExpect<'a, ParticleBuilder>,
-        WriteStorage<'a, SufferDamage>,
+        WriteExpect<'a, EffectQueue>,
         ReadStorage<'a, SingleActivation>,
     );
 
@@ -36,8 +34,7 @@ impl<'a> System<'a> for TriggerSystem {
             entities,
             mut gamelog,
             inflicts_damage,
-            mut particle_builder,
-            mut inflict_damage,
+            mut effects,
             single_activation,
         ) = data;
 
@@ -52,23 +49,32 @@ impl<'a> System<'a> for TriggerSystem {
                         Some(_trigger) => {
                             let name = names.get(*entity_id);
                             if let Some(name) = name {
-                                gamelog
-                                    .entries
-                                    .push(format!("The {} triggers!", &name.name));
+                                gamelog.add(
+                                    LogCategory::Combat,
+                                    format!("The {} triggers!", &name.name),
+                                );
                             }
                             hidden.remove(*entity_id);
 
                             let damage = inflicts_damage.get(*entity_id);
                             if let Some(damage) = damage {
-                                particle_builder.request(
-                                    pos.x,
-                                    pos.y,
-                                    RGB::named(ORANGE),
-                                    RGB::named(BLACK),
-                                    to_cp437('‼'),
-                                    200.0,
+                                effects.add_effect(
+                                    None,
+                                    EffectType::Particle {
+                                        glyph: to_cp437('‼'),
+                                        fg: RGB::named(ORANGE),
+                                        bg: RGB::named(BLACK),
+                                        lifetime: 200.0,
+                                    },
+                                    Targets::Tile { tile_idx: idx },
+                                );
+                                effects.add_effect(
+                                    None,
+                                    EffectType::Damage {
+                                        amount: damage.damage,
+                                    },
+                                    Targets::Single { target: entity },
                                 );
-                                SufferDamage::new_damage(&mut inflict_damage, entity, damage.damage)
                             }
                             let sa = single_activation.get(*entity_id);
                             if let Some(_sa) = sa {
@@ -1,5 +1,8 @@
 use crate::components::{Hidden, Name, Player, Position, Viewshed};
-use crate::{gamelog::GameLog, Map};
+use crate::{
+    gamelog::{GameLog, LogCategory},
+    Map,
+};
 use bracket_lib::prelude::*;
 use specs::prelude::*;
 
@@ -57,7 +60,10 @@ impl<'a> System<'a> for FoVSystem {
                                 if rng.roll_dice(1, 24) == 1 {
                                     let name = names.get(*e);
                                     if let Some(name) = name {
-                                        log.entries.push(format!("You spotted a {}.", &name.name));
+                                        log.add(
+                                            LogCategory::Discovery,
+                                            format!("You spotted a {}.", &name.name),
+                                        );
                                     }
                                     hidden.remove(*e);
                                 }
@@ -0,0 +1,117 @@
+// the same seed and the same commands have to play out the same game, or seeds can't be replayed
+use bracket_lib::prelude::Point;
+use khazad_dum::{
+    components::{CombatStats, Position},
+    game_world::{GameWorld, PlayerCommand},
+    gamelog::{GameLog, LogCategory},
+    map::Map,
+    rng::RunSeed,
+};
+use specs::prelude::*;
+
+/// a walk that bumps into walls, waits and tries the stairs, long enough to meet some monsters
+fn script() -> Vec<PlayerCommand> {
+    let steps = [
+        PlayerCommand::Move { dx: 1, dy: 0 },
+        PlayerCommand::Move { dx: 0, dy: 1 },
+        PlayerCommand::Move { dx: -1, dy: 0 },
+        PlayerCommand::Move { dx: 0, dy: -1 },
+        PlayerCommand::Move { dx: 1, dy: 1 },
+        PlayerCommand::Wait,
+        PlayerCommand::PickUp,
+        PlayerCommand::Descend,
+    ];
+    (0..200)
+        .map(|i| steps[(i * 7 + i / 3) % steps.len()])
+        .collect()
+}
+
+/// everything about a world that two runs of the same seed have to agree on, bar the tiles
+#[derive(PartialEq, Debug)]
+struct Snapshot {
+    depth: i32,
+    player: Point,
+    /// entity id, x, y
+    positions: Vec<(u32, i32, i32)>,
+    /// entity id, hp, max hp, defense, power
+    stats: Vec<(u32, i32, i32, i32, i32)>,
+    /// turn, category, text, count
+    log: Vec<(i32, LogCategory, String, u32)>,
+}
+
+fn snapshot(world: &GameWorld) -> Snapshot {
+    let ecs = &world.ecs;
+    let entities = ecs.entities();
+    let positions = ecs.read_storage::<Position>();
+    let combat_stats = ecs.read_storage::<CombatStats>();
+    let log = ecs.fetch::<GameLog>();
+    let snapshot = Snapshot {
+        depth: world.depth(),
+        player: *ecs.fetch::<Point>(),
+        positions: (&entities, &positions)
+            .join()
+            .map(|(entity, pos)| (entity.id(), pos.x, pos.y))
+            .collect(),
+        stats: (&entities, &combat_stats)
+            .join()
+            .map(|(entity, stats)| {
+                (
+                    entity.id(),
+                    stats.hp,
+                    stats.max_hp,
+                    stats.defense,
+                    stats.power,
+                )
+            })
+            .collect(),
+        log: log
+            .entries()
+            .map(|entry| (entry.turn, entry.category, entry.text.clone(), entry.count))
+            .collect(),
+    };
+    snapshot
+}
+
+fn assert_same_state(a: &GameWorld, b: &GameWorld, turn: usize) {
+    assert_eq!(
+        snapshot(a),
+        snapshot(b),
+        "worlds drifted apart on turn {}",
+        turn
+    );
+    // TileType can't be printed, so the tiles are only checked for equality
+    assert!(
+        a.ecs.fetch::<Map>().tiles == b.ecs.fetch::<Map>().tiles,
+        "levels drifted apart on turn {}",
+        turn
+    );
+}
+
+#[test]
+fn a_seed_builds_the_same_first_level() {
+    let a = GameWorld::new(RunSeed { seed: 1234 });
+    let b = GameWorld::new(RunSeed { seed: 1234 });
+    assert_same_state(&a, &b, 0);
+}
+
+#[test]
+fn a_seed_plays_out_the_same_way() {
+    let mut a = GameWorld::new(RunSeed { seed: 98765 });
+    let mut b = GameWorld::new(RunSeed { seed: 98765 });
+    for (turn, command) in script().into_iter().enumerate() {
+        if a.player_is_dead() || b.player_is_dead() {
+            assert_eq!(a.player_is_dead(), b.player_is_dead(), "turn {}", turn);
+            break;
+        }
+        a.step(command);
+        b.step(command);
+        assert_same_state(&a, &b, turn);
+    }
+}
+
+#[test]
+fn different_seeds_build_different_levels() {
+    let a = GameWorld::new(RunSeed { seed: 1 });
+    let b = GameWorld::new(RunSeed { seed: 2 });
+    assert!(a.ecs.fetch::<Map>().tiles != b.ecs.fetch::<Map>().tiles);
+}